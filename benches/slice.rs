@@ -0,0 +1,50 @@
+//! Compares reading and seeking through `AccReader` and `SliceAccReader` over the same
+//! in-memory data.
+//!
+//! Run with `cargo bench`.
+
+extern crate acc_reader;
+
+use std::hint::black_box;
+use std::io::{BufRead, Seek, SeekFrom};
+use std::time::{Duration, Instant};
+
+use acc_reader::{AccReader, SliceAccReader};
+
+const DATA_LEN: usize = 1024 * 1024;
+const ITERATIONS: u32 = 20;
+
+// Reads the whole stream in small chunks, then seeks around and reads it again.
+fn exercise<R: BufRead + Seek>(mut r: R) -> u64 {
+    let mut sum = 0u64;
+    let mut buf = [0; 256];
+    loop {
+        let n = r.read(&mut buf).unwrap();
+        if n == 0 {
+            break;
+        }
+        sum += buf[..n].iter().map(|&b| b as u64).sum::<u64>();
+    }
+    for i in 0..1000u64 {
+        r.seek(SeekFrom::Start(i * 997 % DATA_LEN as u64)).unwrap();
+        sum += r.fill_buf().unwrap()[0] as u64;
+    }
+    sum
+}
+
+fn bench<F: FnMut() -> u64>(name: &str, mut f: F) {
+    let mut total = Duration::new(0, 0);
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        black_box(f());
+        total += start.elapsed();
+    }
+    println!("{:<16} {:>10.3?} per iteration", name, total / ITERATIONS);
+}
+
+fn main() {
+    let data: Vec<u8> = (0..DATA_LEN).map(|i| i as u8).collect();
+
+    bench("AccReader", || exercise(AccReader::new(&data[..])));
+    bench("SliceAccReader", || exercise(SliceAccReader::new(&data[..])));
+}
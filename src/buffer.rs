@@ -0,0 +1,170 @@
+//! Contains the storage abstraction used by `AccReader`.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use super::io::{self, Read};
+
+/// Storage for the data accumulated by `AccReader`.
+///
+/// An accumulating reader appends everything it reads from the underlying stream to its
+/// buffer and serves reads, `BufRead` slices and seeks from it. `Vec<u8>` is the default
+/// implementation; other implementations can be used with `AccReader::with_custom_buffer()`,
+/// for example to place the accumulated data in a memory-mapped region.
+///
+/// Implementations must uphold the following invariants, which `AccReader` relies on:
+///
+/// * `as_slice()` returns exactly `len()` bytes, which are the bytes stored in the buffer
+///   in the order they were appended (minus any removed with `truncate()` or `drain_front()`);
+/// * the contents of the buffer never change except through the methods of this trait;
+/// * `extend_from_slice()`, `read_from()` and `read_transformed_from()` only ever add bytes
+///   at the end of the buffer;
+/// * `truncate(len)` keeps only the first `len` bytes and does nothing if `len` is not
+///   less than `len()`;
+/// * `drain_front(n)` removes the first `n` bytes, shifting the rest to the beginning;
+///   `AccReader` never calls it with `n` greater than `len()`.
+///
+/// `reserve()` and `capacity()` are only hints which are used for preallocation and
+/// diagnostics; the default implementations do nothing and report the current length.
+pub trait Buffer {
+    /// Returns the number of bytes stored in the buffer.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the buffer contains no bytes.
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the whole contents of the buffer as a contiguous slice.
+    fn as_slice(&self) -> &[u8];
+
+    /// Appends the provided bytes to the end of the buffer.
+    fn extend_from_slice(&mut self, data: &[u8]);
+
+    /// Shortens the buffer, keeping the first `len` bytes.
+    fn truncate(&mut self, len: usize);
+
+    /// Removes the first `n` bytes from the buffer.
+    fn drain_front(&mut self, n: usize);
+
+    /// Reserves capacity for at least `additional` more bytes.
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        let _ = additional;
+    }
+
+    /// Returns the number of bytes the buffer can hold without reallocating.
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+
+    /// Performs a single read of at most `n` bytes from `source`, appending them to the end
+    /// of the buffer, and returns the number of bytes read.
+    ///
+    /// The default implementation reads into a temporary chunk and then appends it with
+    /// `extend_from_slice()`.
+    fn read_from<S: Read + ?Sized>(&mut self, source: &mut S, n: usize) -> io::Result<usize> {
+        let mut chunk = [0; 4096];
+        let len = if n < chunk.len() { n } else { chunk.len() };
+        let read = source.read(&mut chunk[..len])?;
+        self.extend_from_slice(&chunk[..read]);
+        Ok(read)
+    }
+
+    /// Performs a single read of at most `n` bytes from `source` like `read_from()`, but calls
+    /// `transform` with the read bytes before they become a part of the buffer.
+    ///
+    /// The default implementation reads into a temporary chunk, so it reads at most 4096 bytes
+    /// at a time. The implementation for `Vec<u8>` reads directly into the vector and
+    /// transforms the new data in place.
+    fn read_transformed_from<S: Read + ?Sized>(&mut self, source: &mut S, n: usize,
+                                               transform: &mut dyn FnMut(&mut [u8])) -> io::Result<usize> {
+        let mut chunk = [0; 4096];
+        let len = if n < chunk.len() { n } else { chunk.len() };
+        let read = source.read(&mut chunk[..len])?;
+        transform(&mut chunk[..read]);
+        self.extend_from_slice(&chunk[..read]);
+        Ok(read)
+    }
+}
+
+impl Buffer for Vec<u8> {
+    #[inline]
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, data: &[u8]) {
+        Vec::extend_from_slice(self, data)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len)
+    }
+
+    #[inline]
+    fn drain_front(&mut self, n: usize) {
+        self.drain(..n);
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional)
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        Vec::capacity(self)
+    }
+
+    // Reads directly into the spare capacity of the vector. This exposes uninitialized
+    // memory to the source, which well-behaved sources never read from; with
+    // the `safe-buffers` feature the spare capacity is zeroed first instead.
+    #[cfg(not(feature = "safe-buffers"))]
+    #[allow(clippy::uninit_vec)]
+    fn read_from<S: Read + ?Sized>(&mut self, source: &mut S, n: usize) -> io::Result<usize> {
+        let old_len = Vec::len(self);
+        Vec::reserve(self, n);
+        unsafe { self.set_len(old_len + n); }
+
+        let (read, error) = match source.read(&mut self[old_len..]) {
+            Ok(n) => (n, None),
+            Err(e) => (0, Some(e)),
+        };
+        unsafe { self.set_len(old_len + read); }
+
+        if let Some(e) = error {
+            Err(e)
+        } else {
+            Ok(read)
+        }
+    }
+
+    #[cfg(feature = "safe-buffers")]
+    fn read_from<S: Read + ?Sized>(&mut self, source: &mut S, n: usize) -> io::Result<usize> {
+        let old_len = Vec::len(self);
+        self.resize(old_len + n, 0);
+
+        let result = source.read(&mut self[old_len..]);
+        let read = *result.as_ref().unwrap_or(&0);
+        Vec::truncate(self, old_len + read);
+        result
+    }
+
+    fn read_transformed_from<S: Read + ?Sized>(&mut self, source: &mut S, n: usize,
+                                               transform: &mut dyn FnMut(&mut [u8])) -> io::Result<usize> {
+        let old_len = Vec::len(self);
+        let read = Buffer::read_from(self, source, n)?;
+        transform(&mut self[old_len..]);
+        Ok(read)
+    }
+}
@@ -0,0 +1,147 @@
+//! Contains implementations of the `embedded-io` traits for `AccReader`.
+//!
+//! See `EmbeddedSource` documentation for more information and examples.
+
+use embedded_io::{self as eio, Error};
+
+use super::io::{self, BufRead, Read, Seek, SeekFrom};
+use super::{AccReader, Buffer};
+
+/// An adapter which makes an `embedded_io::Read` instance usable as the underlying stream
+/// of `AccReader`.
+///
+/// Together with the `embedded_io` trait implementations of `AccReader`, this makes it
+/// possible to seek over data coming from embedded transports. Errors of the wrapped reader
+/// are converted into I/O errors of the closest kind; the errors returned through
+/// the `embedded_io` traits of `AccReader` are `embedded_io::ErrorKind` values, so the error
+/// messages are not preserved.
+///
+/// This type is only available with the `embedded-io` feature.
+///
+/// # Examples
+///
+/// ```
+/// extern crate acc_reader;
+/// extern crate embedded_io;
+///
+/// use embedded_io::{Read, Seek, SeekFrom};
+///
+/// use acc_reader::{AccReader, EmbeddedSource};
+///
+/// # fn main() {
+/// let input: &[u8] = &[1, 2, 3, 4];
+/// let mut ar = AccReader::new(EmbeddedSource::new(input));
+///
+/// let mut buf = [0; 2];
+/// ar.read_exact(&mut buf).unwrap();
+/// ar.seek(SeekFrom::Start(1)).unwrap();
+/// ar.read_exact(&mut buf).unwrap();
+/// assert_eq!(buf, [2, 3]);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct EmbeddedSource<R> {
+    inner: R,
+}
+
+impl<R: eio::Read> EmbeddedSource<R> {
+    /// Wraps the provided `embedded_io::Read` instance.
+    #[inline]
+    pub fn new(inner: R) -> EmbeddedSource<R> {
+        EmbeddedSource { inner }
+    }
+
+    /// Unwraps this adapter, returning the wrapped reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: eio::Read> Read for EmbeddedSource<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf).map_err(|e| io::Error::from(to_io_kind(e.kind())))
+    }
+}
+
+impl<R: Read, B: Buffer> eio::ErrorType for AccReader<R, B> {
+    type Error = eio::ErrorKind;
+}
+
+impl<R: Read, B: Buffer> eio::Read for AccReader<R, B> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, eio::ErrorKind> {
+        Read::read(self, buf).map_err(to_embedded_kind)
+    }
+}
+
+impl<R: Read, B: Buffer> eio::BufRead for AccReader<R, B> {
+    #[inline]
+    fn fill_buf(&mut self) -> Result<&[u8], eio::ErrorKind> {
+        BufRead::fill_buf(self).map_err(to_embedded_kind)
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        BufRead::consume(self, amt)
+    }
+}
+
+impl<R: Read, B: Buffer> eio::Seek for AccReader<R, B> {
+    fn seek(&mut self, pos: eio::SeekFrom) -> Result<u64, eio::ErrorKind> {
+        let pos = match pos {
+            eio::SeekFrom::Start(n) => SeekFrom::Start(n),
+            eio::SeekFrom::End(n) => SeekFrom::End(n),
+            eio::SeekFrom::Current(n) => SeekFrom::Current(n),
+        };
+        Seek::seek(self, pos).map_err(to_embedded_kind)
+    }
+}
+
+// Only the kinds which exist in all of std, acid_io and embedded-io are preserved.
+fn to_embedded_kind(e: io::Error) -> eio::ErrorKind {
+    match e.kind() {
+        io::ErrorKind::InvalidInput => eio::ErrorKind::InvalidInput,
+        io::ErrorKind::InvalidData => eio::ErrorKind::InvalidData,
+        io::ErrorKind::Interrupted => eio::ErrorKind::Interrupted,
+        io::ErrorKind::WriteZero => eio::ErrorKind::WriteZero,
+        _ => eio::ErrorKind::Other,
+    }
+}
+
+fn to_io_kind(kind: eio::ErrorKind) -> io::ErrorKind {
+    match kind {
+        eio::ErrorKind::InvalidInput => io::ErrorKind::InvalidInput,
+        eio::ErrorKind::InvalidData => io::ErrorKind::InvalidData,
+        eio::ErrorKind::Interrupted => io::ErrorKind::Interrupted,
+        eio::ErrorKind::WriteZero => io::ErrorKind::WriteZero,
+        _ => io::ErrorKind::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_io::{BufRead, ErrorKind, Read, Seek, SeekFrom};
+
+    use super::*;
+
+    #[test]
+    fn test_embedded_acc_reader() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::with_increment(4, EmbeddedSource::new(inner));
+
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6, 7, 0]);
+        reader.consume(3);
+        let mut buf = [0; 2];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0, 1]);
+
+        assert_eq!(reader.seek(SeekFrom::Current(-4)).unwrap(), 1);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [6, 7]);
+        assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 5);
+        assert_eq!(reader.seek(SeekFrom::Start(7)).unwrap_err(), ErrorKind::Other);
+        assert_eq!(reader.seek(SeekFrom::Current(-7)).unwrap_err(), ErrorKind::InvalidInput);
+        assert_eq!(reader.stream_position().unwrap(), 5);
+    }
+}
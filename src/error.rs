@@ -0,0 +1,72 @@
+//! Contains the error details reported by seek operations.
+
+use core::fmt;
+
+use super::io;
+
+/// The reason why a seek operation on an accumulating reader has failed.
+///
+/// Seek errors are returned as `io::Error`s which wrap a value of this type, so the reason
+/// can be recovered by downcasting the result of `io::Error::get_ref()`. The error kinds
+/// are the same as those used by `Seek` implementations without this detail:
+/// `InvalidInput` for `BeforeStart` and `Evicted`, and `UnexpectedEof` for `BeyondEnd`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{Seek, SeekFrom};
+///
+/// use acc_reader::{AccReader, SeekError};
+///
+/// let input: &[u8] = &[1, 2, 3];
+/// let mut ar = AccReader::new(input);
+///
+/// let e = ar.seek(SeekFrom::Current(-1)).unwrap_err();
+/// let reason = e.get_ref().and_then(|e| e.downcast_ref::<SeekError>());
+/// assert_eq!(reason, Some(&SeekError::BeforeStart));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SeekError {
+    /// The target position is before the beginning of the stream.
+    BeforeStart,
+    /// The target position is beyond the end of the stream.
+    BeyondEnd,
+    /// The data at the target position has been discarded and is no longer available,
+    /// e.g. because it was outside of the rewind window.
+    Evicted,
+}
+
+impl SeekError {
+    /// Returns the kind of `io::Error` used to report this error.
+    #[inline]
+    pub fn kind(&self) -> io::ErrorKind {
+        match *self {
+            SeekError::BeforeStart => io::ErrorKind::InvalidInput,
+            SeekError::BeyondEnd => io::ErrorKind::UnexpectedEof,
+            SeekError::Evicted => io::ErrorKind::InvalidInput,
+        }
+    }
+}
+
+impl fmt::Display for SeekError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            SeekError::BeforeStart => "seeking before the beginning of stream",
+            SeekError::BeyondEnd => "seeking beyond end of stream",
+            SeekError::Evicted => "seeking to discarded data",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for SeekError {}
+
+#[cfg(not(feature = "std"))]
+impl io::ErrorTrait for SeekError {}
+
+impl From<SeekError> for io::Error {
+    #[inline]
+    fn from(e: SeekError) -> io::Error {
+        io::Error::new(e.kind(), e)
+    }
+}
@@ -0,0 +1,148 @@
+//! Contains an accumulating reader which reads fixed-size frames.
+//!
+//! See `FramedAccReader` documentation for more information and examples.
+
+use super::io::{self, Read, Seek, SeekFrom};
+use super::{AccReader, Buffer};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// A wrapper around an accumulating reader which reads the stream in frames of `N` bytes.
+///
+/// This is convenient for binary formats consisting of fixed-size records: `read_frame()`
+/// returns each record as an array, and `seek_frames()` moves over whole records. Frames
+/// are counted from the position of the wrapped reader at the moment of wrapping, so
+/// the stream does not have to be aligned to frames from its beginning.
+///
+/// # Examples
+///
+/// ```
+/// use acc_reader::{AccReader, FramedAccReader};
+///
+/// let input: &[u8] = &[1, 2, 3, 4, 5, 6];
+/// let mut fr: FramedAccReader<_, 2> = FramedAccReader::new(AccReader::new(input));
+///
+/// assert_eq!(fr.read_frame().unwrap(), Some([1, 2]));
+/// assert_eq!(fr.read_frame().unwrap(), Some([3, 4]));
+/// fr.seek_frames(-2).unwrap();
+/// assert_eq!(fr.read_frame().unwrap(), Some([1, 2]));
+/// ```
+pub struct FramedAccReader<R: Read, const N: usize, B: Buffer = Vec<u8>> {
+    inner: AccReader<R, B>,
+    // absolute position of the first frame
+    start: u64,
+}
+
+impl<R: Read, const N: usize, B: Buffer> FramedAccReader<R, N, B> {
+    /// Wraps the provided accumulating reader, starting the first frame at its current
+    /// position.
+    ///
+    /// The frame size `N` must be non-zero, which is checked at compile time:
+    ///
+    /// ```compile_fail
+    /// use acc_reader::{AccReader, FramedAccReader};
+    ///
+    /// let input: &[u8] = &[1, 2];
+    /// let fr: FramedAccReader<_, 0> = FramedAccReader::new(AccReader::new(input));
+    /// ```
+    pub fn new(reader: AccReader<R, B>) -> FramedAccReader<R, N, B> {
+        const { assert!(N != 0, "frame size must be non-zero") };
+        let start = reader.absolute_position();
+        FramedAccReader { inner: reader, start }
+    }
+
+    /// Unwraps this reader, returning the wrapped accumulating reader.
+    #[inline]
+    pub fn into_inner(self) -> AccReader<R, B> {
+        self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped accumulating reader.
+    ///
+    /// Moving it to a position which is not on a frame boundary makes the following frames
+    /// start from that position.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut AccReader<R, B> {
+        &mut self.inner
+    }
+
+    /// Returns the index of the frame which is read next.
+    #[inline]
+    pub fn frame_index(&self) -> u64 {
+        self.inner.absolute_position().saturating_sub(self.start) / N as u64
+    }
+
+    /// Reads the next frame, returning `None` if the stream has ended.
+    ///
+    /// If the stream ends in the middle of a frame, an error of kind `UnexpectedEof` is
+    /// returned, and the current position is left unchanged, so the incomplete frame can
+    /// still be read from the wrapped reader.
+    pub fn read_frame(&mut self) -> io::Result<Option<[u8; N]>> {
+        let result = self.inner.read_exact_ref(N).map(|data| {
+            let mut frame = [0; N];
+            frame.copy_from_slice(data);
+            frame
+        });
+        match result {
+            Ok(frame) => Ok(Some(frame)),
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof && self.inner.forward_buffered() == 0 => Ok(None),
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                Err(io::Error::new(io::ErrorKind::UnexpectedEof, "stream ends with an incomplete frame"))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Moves the current position by the specified number of frames, returning the index
+    /// of the frame which is read next.
+    ///
+    /// Seeking fails in the same cases as `Seek::seek()` of the wrapped reader does, and
+    /// additionally with an error of kind `InvalidInput` if the target is before the first
+    /// frame.
+    pub fn seek_frames(&mut self, frames: i64) -> io::Result<u64> {
+        let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "frame offset is too large");
+        let offset = frames.checked_mul(N as i64).ok_or_else(too_large)?;
+        match self.inner.absolute_position().checked_add_signed(offset) {
+            Some(target) if target >= self.start => {}
+            None if offset > 0 => return Err(too_large()),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "seeking before the first frame")),
+        }
+        self.inner.seek(SeekFrom::Current(offset))?;
+        Ok(self.frame_index())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    use super::*;
+
+    #[test]
+    fn test_framed_acc_reader_frames() {
+        let inner: &[u8] = &[9, 5, 6, 7, 0, 1, 2, 3, 4, 8, 8];
+        let mut reader = AccReader::with_increment(3, inner);
+        let mut skipped = [0];
+        reader.read_exact(&mut skipped).unwrap();
+
+        let mut reader: FramedAccReader<_, 4> = FramedAccReader::new(reader);
+        assert_eq!(reader.read_frame().unwrap(), Some([5, 6, 7, 0]));
+        assert_eq!(reader.read_frame().unwrap(), Some([1, 2, 3, 4]));
+        assert_eq!(reader.frame_index(), 2);
+
+        assert_eq!(reader.seek_frames(-1).unwrap(), 1);
+        assert_eq!(reader.read_frame().unwrap(), Some([1, 2, 3, 4]));
+        assert_eq!(reader.seek_frames(-3).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.seek_frames(i64::MAX).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.seek_frames(i64::MAX / 4).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.seek_frames(i64::MIN / 4).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.frame_index(), 2);
+
+        assert_eq!(reader.read_frame().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        let mut rest = Vec::new();
+        reader.get_mut().read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [8, 8]);
+        assert_eq!(reader.read_frame().unwrap(), None);
+    }
+}
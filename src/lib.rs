@@ -1,10 +1,113 @@
 //! Contains an implementation of accumulating reader.
 //!
-//! See `AccReader` documentation for more information and examples.
+//! See `AccReader` documentation for more information and examples. For streams too large
+//! to be kept in memory, see `SpillAccReader`, which stores the accumulated data in a temporary
+//! file beyond a configurable threshold.
+//!
+//! # `no_std` support
+//!
+//! By default this crate is built on top of `std::io`. It can also be used in `no_std`
+//! environments which have an allocator: disable the default `std` feature and enable the
+//! `no_std` one, and `AccReader` will implement the `Read`, `BufRead` and `Seek` traits from
+//! the [`acid_io`](https://docs.rs/acid_io) crate instead. If both features are enabled,
+//! `std` takes precedence.
+//!
+//! `SpillAccReader` and `BoxedAccReader` are not available in this mode. The tests and examples
+//! in this documentation require the `std` feature.
+//!
+//! # Uninitialized memory
+//!
+//! To avoid the cost of zeroing, the default `Vec<u8>` buffer lets the underlying stream read
+//! directly into its uninitialized spare capacity. This is fine for all sources which follow
+//! the `Read` contract and never read from the buffer passed to them, but a misbehaving source
+//! could observe uninitialized memory. The `safe-buffers` feature makes the buffer zero
+//! the memory before each read instead, which is slightly slower but sound with any source.
+//! The behavior of the reader is otherwise the same.
+//!
+//! # Reading into uninitialized buffers
+//!
+//! With the `read_buf` feature, which requires a nightly compiler, `AccReader` implements
+//! `Read::read_buf()`, so that readers which support it, like `BufReader::read_to_end()` in
+//! the standard library, do not have to zero-initialize their buffers before reading from
+//! an accumulating reader.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "read_buf", feature(core_io_borrowed_buf, read_buf))]
 
-use std::io::{self, BufRead, Read, Write, Seek, SeekFrom};
-use std::cmp;
-use std::ptr;
+#[cfg(not(any(feature = "std", feature = "no_std")))]
+compile_error!("either the `std` or the `no_std` feature must be enabled");
+
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+#[cfg(not(feature = "std"))]
+extern crate acid_io;
+#[cfg(feature = "embedded-io")]
+extern crate embedded_io;
+
+#[cfg(feature = "std")]
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+#[cfg(feature = "std")]
+use std::sync::Arc;
+#[cfg(not(feature = "std"))]
+use acid_io::{self as io, BufRead, Read, Seek, SeekFrom, Write};
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use alloc::sync::Arc;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::cmp;
+use core::fmt;
+#[cfg(feature = "hashing")]
+use core::hash::Hasher;
+use core::mem;
+use core::ops::{Index, Range, RangeFrom, RangeFull, RangeTo};
+use core::str;
+
+pub use buffer::Buffer;
+#[cfg(feature = "embedded-io")]
+pub use embedded::EmbeddedSource;
+pub use error::SeekError;
+pub use framed::FramedAccReader;
+pub use shared::SyncAccReader;
+pub use slice::SliceAccReader;
+#[cfg(feature = "std")]
+pub use spill::SpillAccReader;
+
+mod buffer;
+#[cfg(feature = "embedded-io")]
+mod embedded;
+mod error;
+mod framed;
+mod shared;
+mod slice;
+#[cfg(feature = "std")]
+mod spill;
+
+// Generates methods which read integers of fixed size in the specified byte order.
+#[cfg(feature = "byteorder")]
+macro_rules! read_int_methods {
+    ($($name:ident: $ty:ident, $from:ident, $order:expr;)*) => {
+        $(
+            #[doc = concat!("Reads a `", stringify!($ty), "` in ", $order, " byte order.")]
+            ///
+            /// The bytes are read with `read_exact_ref()`, so if the stream ends before all of
+            /// them are available, an error of kind `UnexpectedEof` is returned and the current
+            /// position is left unchanged. This method is only available with the `byteorder`
+            /// feature.
+            pub fn $name(&mut self) -> io::Result<$ty> {
+                let mut bytes = [0; mem::size_of::<$ty>()];
+                bytes.copy_from_slice(self.read_exact_ref(mem::size_of::<$ty>())?);
+                Ok($ty::$from(bytes))
+            }
+        )*
+    }
+}
 
 /// Default capacity for the internal buffer of `AccReader`.
 pub const DEFAULT_BUF_CAPACITY: usize = 4096;
@@ -12,6 +115,46 @@ pub const DEFAULT_BUF_CAPACITY: usize = 4096;
 /// Default increment for the internal buffer of `AccReader`.
 pub const DEFAULT_BUF_INCREMENT: usize = 1024;
 
+/// Maximum number of bytes requested from the stream in one read with `Growth::Doubling`.
+pub const MAX_GROWTH_CHUNK: usize = 1024 * 1024;
+
+/// Strategy for choosing how much data is read from the underlying stream when
+/// the internal buffer of `AccReader` is exhausted.
+///
+/// See `AccReader::with_growth_strategy()` for more information.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Growth {
+    /// Reads the specified number of bytes each time, the same as the buffer increment.
+    Fixed(usize),
+    /// Reads as many bytes as are already buffered, but at least the buffer increment and
+    /// at most `MAX_GROWTH_CHUNK`, so the size of the buffer doubles with each read.
+    Doubling,
+}
+
+/// Policy for deciding when a read from the underlying stream returning zero bytes means
+/// the end of the stream.
+///
+/// See `AccReader::with_eof_policy()` for more information.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EofPolicy {
+    /// The first read returning zero bytes means the end of the stream, as `Read` specifies.
+    FirstZero,
+    /// Up to the specified number of consecutive reads returning zero bytes are retried
+    /// before the end of the stream is reported.
+    Retry(usize),
+}
+
+// A function applied to the data read from the stream, see AccReader::with_transform().
+type Transform = Box<dyn FnMut(&mut [u8]) + Send + Sync>;
+// A function called after each read from the stream, see AccReader::on_source_read().
+type SourceReadCallback = Box<dyn FnMut(usize) + Send + Sync>;
+// A function reporting the progress of long operations, see AccReader::with_progress().
+type ProgressCallback = Box<dyn FnMut(u64) + Send + Sync>;
+#[cfg(feature = "hashing")]
+type BoxedHasher = Box<dyn Hasher + Send + Sync>;
+// Creates a separate instance of an option for each reader built by AccReaderBuilder.
+type Factory<T> = Arc<dyn Fn() -> T + Send + Sync>;
+
 /// An accumulating reader which provides `Seek` for any `Read`.
 ///
 /// An accumulating reader wraps an instance of `std::io::Read` trait and provides 
@@ -29,15 +172,28 @@ pub const DEFAULT_BUF_INCREMENT: usize = 1024;
 /// stream, therefore it will either hang with blocking infinite streams like sockets or will fill
 /// up all of the available memory with truly infinite streams.
 ///
+/// If reading from the underlying stream fails while seeking forward, for example with
+/// `ErrorKind::WouldBlock` on a non-blocking source, the error is returned and the current
+/// position is left unchanged, but whatever was read before the failure stays in the buffer.
+/// Repeating the same seek later resumes reading from where the failed one stopped.
+/// Similarly, `Read::read_exact()` does not consume anything if the stream ends or a read
+/// fails before the whole buffer is filled, unless the buffer is larger than the buffer size
+/// limit, so it can be retried or the data can be read in another way.
+///
 /// This struct will buffer all of the underlying stream in order to provide seeking, therefore
 /// you should discard it as soon as you don't need it if you are working with large streams
-/// of data.
+/// of data. Alternatively, `SpillAccReader` can be used to keep most of the data on disk.
 ///
 /// `AccReader` is parameterized by two values, initial capacity and increment. Initial capacity
 /// defines the initial size of the internal buffer. This buffer automatically grows with each
 /// successful read operation, if necessary, by the number of bytes read. If `BufRead` interface
 /// is used, however, increment value is used to expand the internal buffer capacity when it is
-/// filled.
+/// filled. These and other options, like limits on the buffer size and on how much of the
+/// previously read data is retained, can be configured with `AccReaderBuilder`.
+///
+/// The accumulated data is stored in a `Vec<u8>` by default. Any other storage implementing
+/// the `Buffer` trait can be used instead by constructing the reader with
+/// `AccReader::with_custom_buffer()`.
 ///
 /// # Examples
 ///
@@ -71,12 +227,92 @@ pub const DEFAULT_BUF_INCREMENT: usize = 1024;
 /// let mut input = Vec::new();
 /// ar.read_to_end(&mut input).unwrap();
 /// ```
-pub struct AccReader<R: Read> {
+///
+/// Since `&mut R` implements `Read` whenever `R` does, an accumulating reader can also
+/// borrow a stream instead of owning it. Once the accumulating reader is dropped or unwrapped,
+/// the original stream can be used again; it is positioned after the last byte which the
+/// accumulating reader has read from it, which may be further than the current position
+/// of the accumulating reader because of buffering:
+///
+/// ```
+/// use std::io::{BufRead, Read};
+///
+/// use acc_reader::AccReader;
+///
+/// let mut input: &[u8] = &[1, 2, 3, 4, 5];
+/// {
+///     let mut ar = AccReader::with_increment(3, &mut input);
+///     assert_eq!(ar.fill_buf().unwrap(), &[1, 2, 3]);
+///     ar.consume(1);
+/// }
+/// assert_eq!(input, &[4, 5]);
+/// ```
+pub struct AccReader<R: Read, B: Buffer = Vec<u8>> {
     source: R,
-    buf: Vec<u8>,
+    buf: B,
+    // absolute offset of buf[0] in the stream, non-zero when data is discarded
+    // from the front of the buffer
+    base: u64,
     // invariant: pos <= buf.len()
     pos: usize,
     inc: usize,
+    // number of bytes requested from the source per read, inc if not set
+    read_size: Option<usize>,
+    max_lookahead: Option<usize>,
+    // invariant: buf.len() <= max_buffer
+    max_buffer: Option<usize>,
+    rewind_window: Option<usize>,
+    // absolute offset at which the stream is cut off by truncate_at(),
+    // nothing after it is ever buffered
+    logical_end: Option<u64>,
+    // maximum total number of bytes to be read from the source
+    read_budget: Option<u64>,
+    // total number of bytes read from the source
+    source_read: u64,
+    // length of the stream declared with with_known_length()
+    known_len: Option<u64>,
+    // buffer size above which consumed data is discarded, see with_soft_cap()
+    soft_cap: Option<usize>,
+    // absolute offsets of the starts of all buffered lines, if enabled
+    line_starts: Option<Vec<u64>>,
+    // absolute offset which positions visible through Seek are relative to
+    origin: u64,
+    // whether fill_size() grows with the buffer, see Growth::Doubling
+    doubling: bool,
+    // whether single reads from the source are retried on ErrorKind::Interrupted
+    retry_interrupted: bool,
+    // number of consecutive zero-length reads from the source retried before it is
+    // considered finished, see EofPolicy::Retry
+    zero_read_retries: usize,
+    // minimum number of bytes fill_buf() tries to make available, see with_eager_fill()
+    eager_fill: usize,
+    // whether a repeated fill_buf() at the same position reads more, see with_fill_top_up()
+    fill_top_up: bool,
+    // error from reading ahead while some data was available, returned by the next fill_buf()
+    fill_error: Option<io::Error>,
+    // absolute position and read limit recorded by mark()
+    mark: Option<(u64, usize)>,
+    // absolute position at which fill_buf() has last returned its slice
+    last_fill: u64,
+    // set when the end of the underlying stream has been observed
+    eof: bool,
+    // number of times the capacity of buf has grown
+    reallocs: u64,
+    // maximum length buf has ever had
+    peak_len: usize,
+    // seeks the source if it is seekable, see new_seekable()
+    source_seek: Option<fn(&mut R, SeekFrom) -> io::Result<u64>>,
+    // position of the seekable source corresponding to the absolute offset zero
+    source_start: u64,
+    // called with the number of bytes returned by each read from the source
+    on_source_read: Option<SourceReadCallback>,
+    // called with the number of bytes buffered so far by long operations, see with_progress()
+    progress: Option<ProgressCallback>,
+    // applied to every chunk read from the source before it enters buf
+    transform: Option<Transform>,
+    // fed with every byte entering buf
+    #[cfg(feature = "hashing")]
+    hasher: Option<BoxedHasher>,
 }
 
 impl<R: Read> AccReader<R> {
@@ -158,18 +394,21 @@ impl<R: Read> AccReader<R> {
     /// ```
     #[inline]
     pub fn with_initial_capacity_and_increment(cap: usize, inc: usize, source: R) -> AccReader<R> {
-        AccReader {
-            source: source,
-            buf: Vec::with_capacity(cap),
-            pos: 0,
-            inc: inc,
-        }
+        AccReaderBuilder::new().capacity(cap).increment(inc).build(source)
     }
 
-    /// Unwraps this accumulating reader, returning the underlying `BufRead` instance.
+    /// Creates a new accumulating reader from the provided `Read` instance which never
+    /// buffers more than the specified number of bytes ahead of the current position
+    /// through non-consuming operations.
+    ///
+    /// This limit only affects operations which read data into the internal buffer without
+    /// moving the current position, like `fill_buf()`: they will stop reading once
+    /// `max_lookahead` bytes are available after the current position and return whatever
+    /// is available. In particular, `fill_buf()` will return an empty slice if `max_lookahead`
+    /// is zero. Seeking and reading are not affected.
+    ///
+    /// Default values for the initial buffer capacity and increment are used.
     ///
-    /// Note that any accumulated data will be lost.
-    /// 
     /// # Examples
     ///
     /// ```no_run
@@ -178,227 +417,6644 @@ impl<R: Read> AccReader<R> {
     /// use acc_reader::AccReader;
     ///
     /// let input = io::stdin();
-    /// let mut ar = AccReader::new(input);
+    /// let mut ar = AccReader::with_max_lookahead(64, input);
+    /// ```
+    #[inline]
+    pub fn with_max_lookahead(max_lookahead: usize, source: R) -> AccReader<R> {
+        AccReaderBuilder::new().max_lookahead(Some(max_lookahead)).build(source)
+    }
+
+    /// Creates a new accumulating reader from the provided `Read` instance which requests
+    /// `read_size` bytes from it with each read call.
     ///
-    /// let input2 = ar.into_inner();
+    /// This decouples the size of individual reads from the underlying stream from
+    /// the buffer increment: `fill_buf()` and similar operations request `read_size` bytes
+    /// instead of the increment, reads from the stream made while seeking forward are split
+    /// into requests of at most `read_size` bytes, and `read()` calls with buffers smaller
+    /// than `read_size` are served by reading `read_size` bytes into the internal buffer.
+    /// A large read size reduces the number of calls to slow sources, while a small one
+    /// bounds how much is read at once. Without this option the increment is used for
+    /// `fill_buf()` and other reads are not split.
+    ///
+    /// Default values for the initial buffer capacity and increment are used.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input = io::stdin();
+    /// let mut ar = AccReader::with_read_size(65536, input);
     /// ```
     #[inline]
-    pub fn into_inner(self) -> R {
-        self.source
+    pub fn with_read_size(read_size: usize, source: R) -> AccReader<R> {
+        AccReaderBuilder::new().read_size(Some(read_size)).build(source)
     }
 
-    // Read from the stream into the internal buffer as much as possible,
-    // but no more than the provided number of bytes.
-    // Updates the buffer length to the actual number of bytes read, even
-    // in case of errors.
-    fn read_up_to(&mut self, n: u64) -> io::Result<()> {
-        let old_len = self.buf.len();
-        self.buf.reserve(n as usize);
-        unsafe { self.buf.set_len(old_len + n as usize); }
+    /// Creates a new accumulating reader from the provided `Read` instance which never reads
+    /// more than `budget` bytes from it in total.
+    ///
+    /// This protects against streams which are unexpectedly large, regardless of how much of
+    /// the data is kept in the buffer. Reads from the underlying stream never request more than
+    /// what is left of the budget, and once the budget is exhausted, every operation which needs
+    /// to read from the stream, including seeking beyond the data read so far, fails with
+    /// an error of kind `Other`. Note that this also applies to the read which would detect
+    /// the end of a stream of exactly `budget` bytes.
+    ///
+    /// Default values for the initial buffer capacity and increment are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::with_read_budget(3, input);
+    ///
+    /// let mut data = Vec::new();
+    /// assert!(ar.read_to_end(&mut data).is_err());
+    /// assert_eq!(data, [1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn with_read_budget(budget: u64, source: R) -> AccReader<R> {
+        AccReaderBuilder::new().read_budget(Some(budget)).build(source)
+    }
 
-        let mut error = None;
-        let mut read = 0;
-        {
-            let mut target = &mut self.buf[old_len..];
-            while !target.is_empty() {
-                match self.source.read(target) {
-                    Ok(0) => break,
-                    Ok(n) => { read += n; let tmp = target; target = &mut tmp[n..]; }
-                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
-                    Err(e) => { error = Some(e); break; },
-                }
-            }
-        }
-        unsafe { self.buf.set_len(old_len + read as usize); }
+    /// Creates a new accumulating reader from the provided `Read` instance whose total length
+    /// is known to be `len` bytes, e.g. from the metadata of a file.
+    ///
+    /// This makes seeking with `SeekFrom::End` cheaper: the target is computed from the declared
+    /// length, so only the data up to the target is read from the underlying stream instead of
+    /// the whole stream. If the stream turns out to be shorter than declared, such a seek fails
+    /// with an error of kind `UnexpectedEof`. Once the end of the stream has actually been
+    /// reached, its real length is used instead.
+    ///
+    /// Default values for the initial buffer capacity and increment are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::with_known_length(5, input);
+    ///
+    /// assert_eq!(ar.seek(SeekFrom::End(-2)).unwrap(), 3);
+    /// let mut buf = [0; 2];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert_eq!(buf, [4, 5]);
+    /// ```
+    #[inline]
+    pub fn with_known_length(len: u64, source: R) -> AccReader<R> {
+        AccReaderBuilder::new().known_length(Some(len)).build(source)
+    }
 
-        if let Some(e) = error {
-            Err(e)
-        } else {
-            Ok(())
-        }
+    /// Creates a new accumulating reader from the provided `Read` instance which discards
+    /// the consumed data once the internal buffer grows beyond `cap` bytes.
+    ///
+    /// Unlike `AccReaderBuilder::max_buffer()`, this never causes errors: it is a best-effort
+    /// way to keep the memory usage bounded when the reader is mostly used for reading forward,
+    /// e.g. through `fill_buf()` and `consume()`. Each time data is consumed with `consume()` or
+    /// `read()` while the buffer holds more than `cap` bytes, everything before the current
+    /// position is discarded, except for the rewind window if one is configured with
+    /// `AccReaderBuilder::rewind_window()`. Seeking back to the discarded data fails with
+    /// `SeekError::Evicted`, so only the data after the current position, plus the rewind
+    /// window, stays seekable while the buffer is over the cap. Positions stay absolute.
+    ///
+    /// Default values for the initial buffer capacity and increment are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{BufRead, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::with_soft_cap(2, input);
+    ///
+    /// assert_eq!(ar.fill_buf().unwrap(), &[1, 2, 3, 4, 5]);
+    /// ar.consume(2);
+    /// assert_eq!(ar.as_ref(), &[3, 4, 5]);
+    /// assert!(ar.seek(SeekFrom::Start(1)).is_err());
+    /// ```
+    #[inline]
+    pub fn with_soft_cap(cap: usize, source: R) -> AccReader<R> {
+        AccReaderBuilder::new().soft_cap(Some(cap)).build(source)
     }
-}
 
-impl<R: Read> Read for AccReader<R> {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        let need_to_read = cmp::min(self.buf.len() - self.pos, buf.len());
-        if need_to_read > 0 {
-            unsafe {
-                ptr::copy_nonoverlapping(
-                    self.buf.as_ptr().offset(self.pos as isize), 
-                    buf.as_mut_ptr(), 
-                    need_to_read
-                );
-            }
-            self.pos += need_to_read;
-            Ok(need_to_read)
-        } else {  // need_to_read == 0
-            let read = try!(self.source.read(buf));
-            let _ = self.buf.write_all(&buf[..read]);
-            self.pos += read;
-            Ok(read)
-        }
+    /// Creates a new accumulating reader from the provided `Read` instance which maintains
+    /// an index of the offsets at which lines start.
+    ///
+    /// The index is updated whenever data is read from the underlying stream, so it covers
+    /// everything buffered so far, and makes it possible to jump to a specific line with
+    /// `seek_to_line()` without scanning the data again. Note that the index takes eight bytes
+    /// per line, and it is retained even when the data itself is discarded.
+    ///
+    /// Default values for the initial buffer capacity and increment are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufRead;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = b"first\nsecond\nthird\n";
+    /// let mut ar = AccReader::with_line_index(input);
+    ///
+    /// assert_eq!(ar.seek_to_line(2).unwrap(), 13);
+    /// assert_eq!(ar.line_offset(1), Some(6));
+    ///
+    /// let mut line = String::new();
+    /// ar.read_line(&mut line).unwrap();
+    /// assert_eq!(line, "third\n");
+    /// ```
+    #[inline]
+    pub fn with_line_index(source: R) -> AccReader<R> {
+        AccReaderBuilder::new().line_index(true).build(source)
     }
-}
 
-impl<R: Read> BufRead for AccReader<R> {
-    fn fill_buf(&mut self) -> io::Result<&[u8]> {
-        let available = self.buf.len() - self.pos;  // self.buf.len() >= pos
-        if available == 0 {
-            let old_len = self.buf.len();
-            self.buf.reserve(self.inc);
-            unsafe { self.buf.set_len(old_len + self.inc); }
-
-            let (read, error) = match self.source.read(&mut self.buf[self.pos..]) {
-                Ok(n) => (n, None),
-                Err(e) => (0, Some(e)),
-            };
-            unsafe { self.buf.set_len(old_len + read); }
+    /// Creates a new accumulating reader from the provided `Read` instance and immediately
+    /// buffers the first `prefill` bytes of it.
+    ///
+    /// If the stream is shorter than `prefill` bytes, all of it is buffered and the reader is
+    /// returned as usual. An error is returned only if reading from the stream fails, in which
+    /// case the stream is dropped.
+    ///
+    /// Default values for the initial buffer capacity and increment are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let ar = AccReader::with_prefill(2, input).unwrap();
+    /// assert_eq!(ar.buffer(), &[1, 2]);
+    /// ```
+    pub fn with_prefill(prefill: usize, source: R) -> io::Result<AccReader<R>> {
+        let mut reader = AccReader::new(source);
+        reader.read_up_to(prefill as u64)?;
+        Ok(reader)
+    }
 
-            if let Some(e) = error {
-                Err(e)
-            } else {
-                Ok(&self.buf[self.pos..])
-            }
-        } else {
-            Ok(&self.buf[self.pos..])
-        }
+    /// Creates a new accumulating reader from the provided `Read` instance which reuses
+    /// the allocation of the provided vector as its internal buffer.
+    ///
+    /// The vector is cleared, but its capacity is kept. Together with `into_parts()`, this
+    /// allows reusing one allocation for many short-lived readers instead of allocating
+    /// a new buffer for each of them. The default value for the increment is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let mut buf = Vec::with_capacity(1024);
+    /// for input in [&b"first"[..], b"second"] {
+    ///     let mut ar = AccReader::with_buffer(buf, input);
+    ///     let mut data = Vec::new();
+    ///     ar.read_to_end(&mut data).unwrap();
+    ///     assert_eq!(data, input);
+    ///     buf = ar.into_parts().0;
+    /// }
+    /// assert!(buf.capacity() >= 1024);
+    /// ```
+    #[inline]
+    pub fn with_buffer(buf: Vec<u8>, source: R) -> AccReader<R> {
+        AccReader::with_custom_buffer(buf, source)
     }
 
-    fn consume(&mut self, amt: usize) {
-        self.pos = cmp::min(self.pos + amt, self.buf.len());
+    /// Creates a new accumulating reader from the provided `Read` instance which feeds all
+    /// of the data read from it to the provided hasher.
+    ///
+    /// Every byte is hashed once, when it is read from the underlying stream, in the order of
+    /// the stream; reading the same data again after seeking back does not affect the hash.
+    /// The hash of everything read so far is returned by `finish_hash()`, which makes it
+    /// possible to check the integrity of the data without another pass over it.
+    ///
+    /// Default values for the initial buffer capacity and increment are used. This constructor
+    /// is only available with the `hashing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "hashing")] {
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::Hasher;
+    /// use std::io::{Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::with_hasher(DefaultHasher::new(), input);
+    ///
+    /// let mut data = Vec::new();
+    /// ar.read_to_end(&mut data).unwrap();
+    /// ar.seek(SeekFrom::Start(0)).unwrap();
+    /// ar.read_to_end(&mut data).unwrap();
+    ///
+    /// let mut expected = DefaultHasher::new();
+    /// expected.write(&[1, 2, 3]);
+    /// assert_eq!(ar.finish_hash(), Some(expected.finish()));
+    /// # }
+    /// ```
+    #[cfg(feature = "hashing")]
+    pub fn with_hasher<H>(hasher: H, source: R) -> AccReader<R>
+        where H: Hasher + Send + Sync + 'static
+    {
+        let mut reader = AccReader::new(source);
+        reader.hasher = Some(Box::new(hasher));
+        reader
     }
-}
 
-impl<R: Read> Seek for AccReader<R> {
-    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
-        match pos {
-            SeekFrom::End(n) => {
-                if n > 0 {
-                    Err(io::Error::new(io::ErrorKind::UnexpectedEof, "seeking beyond end of stream"))
-                } else {
-                    // just read everything that's left and seek from that
-                    try!(self.source.read_to_end(&mut self.buf));
-
-                    let d = (-n) as u64;
-                    if d > self.buf.len() as u64 {
-                        Err(io::Error::new(io::ErrorKind::InvalidInput, "seeking before the begining of stream"))
-                    } else {
-                        self.pos = (self.buf.len() as u64 - d) as usize;
-                        Ok(self.pos as u64)
-                    }
-                }
-            }
-            SeekFrom::Start(n) if n <= self.buf.len() as u64 => {
-                self.pos = n as usize;
-                Ok(self.pos as u64)
-            }
-            SeekFrom::Start(n) => { // n > self.buf.len()
-                let need_to_read = n - self.buf.len() as u64;
-                try!(self.read_up_to(need_to_read));
-                if n > self.buf.len() as u64 {  // still not enough
-                    Err(io::Error::new(io::ErrorKind::UnexpectedEof, "seeking beyond end of stream"))
-                } else {
-                    self.pos = n as usize;
-                    Ok(n)
-                }
-            }
-            SeekFrom::Current(0) => { Ok(self.pos as u64) }
-            SeekFrom::Current(n) if n < 0 => {
-                let d = (-n) as u64;
-                if d > self.pos as u64 {
-                    Err(io::Error::new(io::ErrorKind::InvalidInput, "seeking before the beginning of stream"))
-                } else {
-                    self.pos = (self.pos as u64 - d) as usize;
-                    Ok(self.pos as u64)
-                }
-            }
-            SeekFrom::Current(n) => {  // n > 0
-                let new_pos = self.pos as u64 + n as u64;
-                if new_pos > self.buf.len() as u64 {
-                    let need_to_read = new_pos - self.buf.len() as u64;
-                    try!(self.read_up_to(need_to_read));
-                    if new_pos > self.buf.len() as u64 {  // still not enough
-                        Err(io::Error::new(io::ErrorKind::UnexpectedEof, "seeking beyond end of stream"))
-                    } else {
-                        self.pos = new_pos as usize;
-                        Ok(new_pos)
-                    }
-                } else {
-                    self.pos = new_pos as usize;
-                    Ok(self.pos as u64)
-                }
-            }
-        }
+    /// Creates a new accumulating reader from the provided `Read` instance which applies
+    /// the provided function to all of the data read from it, e.g. to decrypt it.
+    ///
+    /// The function is called with each chunk of data read from the underlying stream,
+    /// before the chunk enters the internal buffer, and may modify it in place. The transformed
+    /// data is what is accumulated, read and seeked over. Every byte is transformed exactly
+    /// once, in the order of the stream: reading the same data again after seeking back
+    /// returns the same transformed bytes without calling the function. The chunks may have any
+    /// size, so the function has to keep its own state, like a keystream position, if it
+    /// needs one. Bytes inserted with `push_front()` are not transformed.
+    ///
+    /// Default values for the initial buffer capacity and increment are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[0x31, 0x32, 0x33];
+    /// let mut ar = AccReader::with_transform(|chunk: &mut [u8]| {
+    ///     for b in chunk {
+    ///         *b ^= 0x30;
+    ///     }
+    /// }, input);
+    ///
+    /// let mut data = Vec::new();
+    /// ar.read_to_end(&mut data).unwrap();
+    /// ar.seek(SeekFrom::Start(1)).unwrap();
+    /// ar.read_to_end(&mut data).unwrap();
+    /// assert_eq!(data, [1, 2, 3, 2, 3]);
+    /// ```
+    pub fn with_transform<F>(transform: F, source: R) -> AccReader<R>
+        where F: FnMut(&mut [u8]) + Send + Sync + 'static
+    {
+        let mut reader = AccReader::new(source);
+        reader.transform = Some(Box::new(transform));
+        reader
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use std::io::{self, BufRead, Read, Seek, SeekFrom};
+/// An accumulating reader over a boxed trait object.
+///
+/// This is convenient when readers of different types are handled uniformly. It requires
+/// the `std` feature, because `acid_io` does not implement `Read` for boxed trait objects.
+#[cfg(feature = "std")]
+pub type BoxedAccReader<'a> = AccReader<Box<dyn Read + 'a>>;
 
-    use super::*;
+/// An accumulating reader over a boxed trait object which can be sent to other threads.
+#[cfg(feature = "std")]
+pub type BoxedAccReaderSend<'a> = AccReader<Box<dyn Read + Send + 'a>>;
 
-    #[test]
-    fn test_acc_reader_read() {
-        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3];
-        let mut reader = AccReader::new(inner);
+#[cfg(feature = "std")]
+impl<'a> AccReader<Box<dyn Read + 'a>> {
+    /// Creates a new accumulating reader which reads from the provided `Read` instance
+    /// through a boxed trait object.
+    ///
+    /// Default values for the initial buffer capacity and increment are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    ///
+    /// use acc_reader::{AccReader, BoxedAccReader};
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let readers: Vec<BoxedAccReader> = vec![
+    ///     AccReader::boxed(input),
+    ///     AccReader::boxed(io::repeat(0)),
+    /// ];
+    /// ```
+    #[inline]
+    pub fn boxed<R: Read + 'a>(source: R) -> BoxedAccReader<'a> {
+        AccReader::new(Box::new(source))
+    }
+}
 
-        let mut buf = [0, 0];
-        assert_eq!(reader.read(&mut buf).unwrap(), 2);
-        assert_eq!(buf, [5, 6]);
-        assert_eq!(reader.read(&mut buf).unwrap(), 2);
-        assert_eq!(buf, [7, 0]);
-        assert_eq!(reader.read(&mut buf).unwrap(), 2);
-        assert_eq!(buf, [1, 2]);
-        assert_eq!(reader.read(&mut buf).unwrap(), 1);
-        assert_eq!(buf[0], 3);
+#[cfg(feature = "std")]
+impl<'a> AccReader<Box<dyn Read + Send + 'a>> {
+    /// Creates a new accumulating reader which reads from the provided `Read` instance
+    /// through a boxed trait object, keeping the reader `Send`.
+    ///
+    /// Default values for the initial buffer capacity and increment are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    /// use std::thread;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &'static [u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::boxed_send(input);
+    ///
+    /// let data = thread::spawn(move || {
+    ///     let mut data = Vec::new();
+    ///     ar.read_to_end(&mut data).map(|_| data)
+    /// }).join().unwrap().unwrap();
+    /// assert_eq!(data, [1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn boxed_send<R: Read + Send + 'a>(source: R) -> BoxedAccReaderSend<'a> {
+        AccReader::new(Box::new(source))
     }
+}
 
-    #[test]
+#[cfg(feature = "std")]
+impl<A: Read, S: Read> AccReader<io::Chain<A, S>> {
+    /// Creates a new accumulating reader which reads the first stream to its end and then
+    /// the second one, like `AccReader::new(first.chain(second))`.
+    ///
+    /// The accumulated data spans both streams transparently: offsets continue from the end
+    /// of the first stream into the second one, and seeking back across the boundary works
+    /// like anywhere else. Default values for the initial buffer capacity and increment
+    /// are used.
+    ///
+    /// This method is only available with the `std` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let header: &[u8] = &[1, 2];
+    /// let body: &[u8] = &[3, 4, 5];
+    /// let mut ar = AccReader::from_chain(header, body);
+    ///
+    /// ar.seek(SeekFrom::Start(1)).unwrap();
+    /// let mut buf = [0; 3];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert_eq!(buf, [2, 3, 4]);
+    /// ```
+    #[inline]
+    pub fn from_chain(first: A, second: S) -> AccReader<io::Chain<A, S>> {
+        AccReader::new(first.chain(second))
+    }
+}
+
+impl<R: Read + Seek> AccReader<R> {
+    /// Creates a new accumulating reader from the provided stream which is itself seekable,
+    /// seeking it instead of buffering when possible.
+    ///
+    /// Reading and seeking within the buffered data and forward work as usual, but
+    /// `SeekFrom::End` asks the underlying stream for its length instead of buffering the whole
+    /// stream, and if the target is not buffered yet, the underlying stream is seeked to it.
+    /// In that case, as well as when seeking back to data which has been discarded
+    /// from the buffer, e.g. because of a rewind window, the buffered data is dropped and
+    /// accumulation restarts at the target. This makes reading the end of a large file cheap.
+    /// The current position of the stream becomes the offset zero.
+    ///
+    /// Replacing or appending an underlying stream turns this behavior off. Default values
+    /// for the initial buffer capacity and increment are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Cursor, Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input = Cursor::new(vec![0; 1 << 20]);
+    /// let mut ar = AccReader::new_seekable(input).unwrap();
+    ///
+    /// assert_eq!(ar.seek(SeekFrom::End(-4)).unwrap(), (1 << 20) - 4);
+    /// let mut buf = [1; 4];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert_eq!(buf, [0; 4]);
+    /// assert!(ar.as_ref().len() < 1 << 20);
+    /// ```
+    pub fn new_seekable(mut source: R) -> io::Result<AccReader<R>> {
+        let start = source.stream_position()?;
+        let mut reader = AccReader::new(source);
+        reader.source_seek = Some(R::seek);
+        reader.source_start = start;
+        Ok(reader)
+    }
+}
+
+impl<R: Read, B: Buffer> AccReader<R, B> {
+    /// Creates a new accumulating reader from the provided `Read` instance which stores
+    /// the accumulated data in the provided buffer.
+    ///
+    /// Any data already present in the buffer is discarded. The default value for the
+    /// increment is used; see `AccReaderBuilder::build_with_custom_buffer()` to configure it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input = io::stdin();
+    /// let mut ar = AccReader::with_custom_buffer(Vec::with_capacity(512), input);
+    /// ```
+    #[inline]
+    pub fn with_custom_buffer(buf: B, source: R) -> AccReader<R, B> {
+        AccReaderBuilder::new().build_with_custom_buffer(buf, source)
+    }
+
+    /// Sets a function which is called with the number of bytes returned by each read from
+    /// the underlying stream, including the reads returning zero bytes at the end of the stream.
+    ///
+    /// Failed reads are not reported. This is intended for tracing and testing read patterns;
+    /// it replaces any previously set function.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufRead;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let reads = Arc::new(Mutex::new(Vec::new()));
+    /// let log = reads.clone();
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::with_increment(2, input)
+    ///     .on_source_read(move |n| log.lock().unwrap().push(n));
+    ///
+    /// ar.fill_buf().unwrap();
+    /// assert_eq!(*reads.lock().unwrap(), [2]);
+    /// ```
+    pub fn on_source_read<F>(mut self, f: F) -> AccReader<R, B>
+        where F: FnMut(usize) + Send + Sync + 'static
+    {
+        self.on_source_read = Some(Box::new(f));
+        self
+    }
+
+    /// Sets a function which reports the progress of operations which may read a lot of data
+    /// from the underlying stream, like seeking with `SeekFrom::End` or far forward.
+    ///
+    /// The function is called after each read from the stream made by such an operation
+    /// with the total number of bytes it has buffered so far, so it can be used to show
+    /// a progress bar while a large stream is being buffered. It replaces any previously set
+    /// function.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Seek, SeekFrom};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let progress = Arc::new(Mutex::new(Vec::new()));
+    /// let log = progress.clone();
+    ///
+    /// let input: &[u8] = &[0; 10000];
+    /// let mut ar = AccReader::new(input).with_progress(move |n| log.lock().unwrap().push(n));
+    ///
+    /// ar.seek(SeekFrom::End(0)).unwrap();
+    /// assert_eq!(progress.lock().unwrap().last(), Some(&10000));
+    /// ```
+    pub fn with_progress<F>(mut self, f: F) -> AccReader<R, B>
+        where F: FnMut(u64) + Send + Sync + 'static
+    {
+        self.progress = Some(Box::new(f));
+        self
+    }
+
+    /// Sets how much data is read from the underlying stream each time the internal buffer
+    /// is exhausted, e.g. by `fill_buf()`.
+    ///
+    /// The default is `Growth::Fixed` with the buffer increment. Reading a fixed amount each
+    /// time means that streaming a large input through `BufRead` takes a number of reads
+    /// proportional to its size, each of which may also grow the buffer; with
+    /// `Growth::Doubling`, both the reads and the reallocations are amortized. `Growth::Fixed`
+    /// replaces the buffer increment. If the read size is set with
+    /// `AccReaderBuilder::read_size()`, it takes precedence over this strategy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufRead;
+    ///
+    /// use acc_reader::{AccReader, Growth};
+    ///
+    /// let input: &[u8] = &[0; 16];
+    /// let mut ar = AccReader::with_increment(4, input).with_growth_strategy(Growth::Doubling);
+    ///
+    /// assert_eq!(ar.fill_buf().unwrap().len(), 4);
+    /// ar.consume(4);
+    /// assert_eq!(ar.fill_buf().unwrap().len(), 4);
+    /// ar.consume(4);
+    /// assert_eq!(ar.fill_buf().unwrap().len(), 8);
+    /// ```
+    pub fn with_growth_strategy(mut self, growth: Growth) -> AccReader<R, B> {
+        match growth {
+            Growth::Fixed(inc) => {
+                self.inc = inc;
+                self.doubling = false;
+            }
+            Growth::Doubling => self.doubling = true,
+        }
+        self
+    }
+
+    /// Sets whether reads from the underlying stream failing with `ErrorKind::Interrupted`
+    /// are retried transparently, which is the default.
+    ///
+    /// This affects the operations which read from the underlying stream only once, like
+    /// `Read::read()` and `BufRead::fill_buf()`. When it is disabled, they return such errors
+    /// to the caller, the same as the underlying stream would. Operations which read repeatedly,
+    /// like `Read::read_exact()` or seeking, always retry interrupted reads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufRead;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input).with_retry_interrupted(false);
+    ///
+    /// assert_eq!(ar.fill_buf().unwrap(), &[1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn with_retry_interrupted(mut self, retry: bool) -> AccReader<R, B> {
+        self.retry_interrupted = retry;
+        self
+    }
+
+    /// Sets how many reads from the underlying stream returning zero bytes are needed
+    /// to consider the stream finished.
+    ///
+    /// The default is `EofPolicy::FirstZero`, as `Read` specifies. Some misbehaving streams,
+    /// however, transiently return zero bytes before the end; with `EofPolicy::Retry`, such
+    /// reads are repeated, up to the specified number of times in a row, before the end
+    /// of the stream is reported. Every read, including the retried ones, is reported to
+    /// the function set with `on_source_read()`. With a retry policy, all reads go through
+    /// the internal buffer, even for the large `Read::read()` calls which would otherwise
+    /// read from the stream directly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufRead;
+    ///
+    /// use acc_reader::{AccReader, EofPolicy};
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input).with_eof_policy(EofPolicy::Retry(2));
+    ///
+    /// assert_eq!(ar.fill_buf().unwrap(), &[1, 2, 3]);
+    /// ar.consume(3);
+    /// assert!(ar.fill_buf().unwrap().is_empty());
+    /// ```
+    #[inline]
+    pub fn with_eof_policy(mut self, policy: EofPolicy) -> AccReader<R, B> {
+        self.zero_read_retries = match policy {
+            EofPolicy::FirstZero => 0,
+            EofPolicy::Retry(n) => n,
+        };
+        self
+    }
+
+    /// Makes `BufRead::fill_buf()` read from the underlying stream until at least `min` bytes
+    /// are available after the current position, even if some data is already buffered.
+    ///
+    /// By default, `fill_buf()` only reads when there is no buffered data after the current
+    /// position, so a parser which needs a larger window than what is left has to go through
+    /// more `fill_buf()` and `consume()` cycles. With eager filling, fewer bytes are returned
+    /// only at the end of the stream, or if reading fails after some data is already available;
+    /// in the latter case, the error is returned by the next call, unless it is of kind
+    /// `WouldBlock`. The maximum lookahead, if set, takes precedence over `min`. Zero,
+    /// the default, disables eager filling.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufRead;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[0; 10];
+    /// let mut ar = AccReader::with_increment(4, input).with_eager_fill(6);
+    ///
+    /// assert_eq!(ar.fill_buf().unwrap().len(), 6);
+    /// ar.consume(4);
+    /// assert_eq!(ar.fill_buf().unwrap().len(), 6);
+    /// ar.consume(4);
+    /// assert_eq!(ar.fill_buf().unwrap().len(), 2);
+    /// ```
+    #[inline]
+    pub fn with_eager_fill(mut self, min: usize) -> AccReader<R, B> {
+        self.eager_fill = min;
+        self
+    }
+
+    /// Makes `BufRead::fill_buf()` read more data when it is called again at the same position
+    /// and less than the increment is available.
+    ///
+    /// A repeated call without anything consumed in between means that the caller needs more
+    /// than what has been returned, so with this option a parser can make progress through
+    /// `fill_buf()` alone. The newly read data is appended to what has already been returned.
+    /// If reading fails, the available data is returned anyway, and the error is returned by
+    /// the next call. Unlike eager filling, this
+    /// never reads if the caller is satisfied with what is available, but every repeated call
+    /// may block on the underlying stream. This is disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufRead;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::with_increment(3, input).with_fill_top_up(true);
+    ///
+    /// assert_eq!(ar.fill_buf().unwrap(), &[1, 2, 3]);
+    /// ar.consume(2);
+    /// assert_eq!(ar.fill_buf().unwrap(), &[3]);
+    /// assert_eq!(ar.fill_buf().unwrap(), &[3, 4, 5]);
+    /// ```
+    #[inline]
+    pub fn with_fill_top_up(mut self, top_up: bool) -> AccReader<R, B> {
+        self.fill_top_up = top_up;
+        self
+    }
+
+    /// Replaces the underlying stream with the provided one, returning the old stream.
+    ///
+    /// The accumulated data and the current position are kept, and further reads from
+    /// the stream continue from the new one. The new stream must continue exactly where
+    /// the old one has stopped, that is, its first byte must be the one at offset
+    /// `stream_position() + buffer().len()` of the whole stream; otherwise, the data read
+    /// later will not match the offsets. This is useful for resuming an interrupted transfer,
+    /// e.g. by requesting the rest of a file from a server after a connection failure.
+    ///
+    /// Since the new stream may have more data, the end of the old stream is forgotten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{BufRead, Read};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let first: &[u8] = &[1, 2];
+    /// let second: &[u8] = &[3, 4];
+    /// let mut ar = AccReader::new(first);
+    ///
+    /// ar.fill_buf().unwrap();
+    /// ar.replace_source(second);
+    ///
+    /// let mut data = Vec::new();
+    /// ar.read_to_end(&mut data).unwrap();
+    /// assert_eq!(data, [1, 2, 3, 4]);
+    /// ```
+    pub fn replace_source(&mut self, new_source: R) -> R {
+        self.eof = false;
+        self.source_seek = None;
+        let old_source = mem::replace(&mut self.source, new_source);
+        self.debug_check_invariants();
+        old_source
+    }
+
+    /// Adds another stream to be read after the end of the underlying stream, continuing
+    /// the accumulation into the same buffer.
+    ///
+    /// The returned reader keeps all of the buffered data, the current position and
+    /// the configuration of this reader; its underlying stream is the chain of the current
+    /// one and `next`. Since the stream gets longer, the end of the current stream is
+    /// forgotten, and so is the length declared with `with_known_length()`.
+    ///
+    /// This method is only available with the `std` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let first: &[u8] = &[1, 2];
+    /// let second: &[u8] = &[3, 4];
+    /// let mut ar = AccReader::new(first);
+    /// assert_eq!(ar.seek(SeekFrom::End(0)).unwrap(), 2);
+    ///
+    /// let mut ar = ar.append_source(second);
+    /// let mut data = Vec::new();
+    /// ar.read_to_end(&mut data).unwrap();
+    /// assert_eq!(data, [3, 4]);
+    /// assert_eq!(ar.seek(SeekFrom::End(-3)).unwrap(), 1);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn append_source<S: Read>(self, next: S) -> AccReader<io::Chain<R, S>, B> {
+        let AccReader {
+            source, buf, base, pos, inc, read_size, max_lookahead, max_buffer, rewind_window,
+            logical_end, read_budget, source_read, known_len: _, soft_cap, line_starts, origin,
+            doubling, retry_interrupted, zero_read_retries, eager_fill, fill_top_up, fill_error, mark,
+            last_fill, eof: _, reallocs, peak_len, source_seek: _, source_start: _, on_source_read,
+            progress, transform,
+            #[cfg(feature = "hashing")]
+            hasher,
+        } = self;
+        AccReader {
+            source: source.chain(next),
+            buf, base, pos, inc, read_size, max_lookahead, max_buffer, rewind_window,
+            logical_end, read_budget, source_read, known_len: None, soft_cap, line_starts, origin,
+            doubling, retry_interrupted, zero_read_retries, eager_fill, fill_top_up, fill_error, mark,
+            last_fill, eof: false, reallocs, peak_len, source_seek: None, source_start: 0, on_source_read,
+            progress, transform,
+            #[cfg(feature = "hashing")]
+            hasher,
+        }
+    }
+
+    /// Returns the hash of all of the data read from the underlying stream so far, or `None`
+    /// if this reader was not created with `with_hasher()`.
+    ///
+    /// This method is only available with the `hashing` feature.
+    #[cfg(feature = "hashing")]
+    #[inline]
+    pub fn finish_hash(&self) -> Option<u64> {
+        self.hasher.as_ref().map(|h| h.finish())
+    }
+
+    /// Unwraps this accumulating reader, returning the underlying `BufRead` instance.
+    ///
+    /// Note that any accumulated data will be lost.
+    /// 
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input = io::stdin();
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let input2 = ar.into_inner();
+    /// ```
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+
+    /// Unwraps this accumulating reader, returning the data buffered after the current
+    /// position together with the underlying `Read` instance.
+    ///
+    /// Unlike `into_inner()`, this does not lose the data which has been read from the stream
+    /// ahead of the current position: the rest of the stream consists of the returned bytes
+    /// followed by whatever is left in the underlying stream, so the two can be combined
+    /// with `Read::chain()`. The data before the current position is still discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{BufRead, Read};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::with_increment(3, input);
+    ///
+    /// ar.fill_buf().unwrap();
+    /// ar.consume(1);
+    ///
+    /// let (unread, input2) = ar.into_inner_with_unread();
+    /// assert_eq!(unread, [2, 3]);
+    ///
+    /// let mut rest = Vec::new();
+    /// (&unread[..]).chain(input2).read_to_end(&mut rest).unwrap();
+    /// assert_eq!(rest, [2, 3, 4, 5]);
+    /// ```
+    pub fn into_inner_with_unread(self) -> (Vec<u8>, R) {
+        (self.buf.as_slice()[self.pos..].to_vec(), self.source)
+    }
+
+    /// Unwraps this accumulating reader, returning the internal buffer together with
+    /// the underlying `Read` instance.
+    ///
+    /// The buffer contains all of the data retained by this reader, starting from
+    /// the offset returned by `buffer_start_offset()`. It can be passed to `with_buffer()`
+    /// or `with_custom_buffer()` to reuse its allocation for another reader.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let mut buf = [0; 2];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// let (data, _) = ar.into_parts();
+    /// assert_eq!(data, [1, 2]);
+    /// ```
+    #[inline]
+    pub fn into_parts(self) -> (B, R) {
+        (self.buf, self.source)
+    }
+
+    /// Unwraps this accumulating reader, returning the underlying `Read` instance only if
+    /// the whole stream has been consumed.
+    ///
+    /// If there are any bytes left after the current position, either in the internal buffer
+    /// or in the underlying stream, an error of kind `InvalidData` is returned instead. Note
+    /// that checking the underlying stream may require reading one more byte from it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let mut buf = Vec::new();
+    /// ar.read_to_end(&mut buf).unwrap();
+    ///
+    /// let input2 = ar.into_inner_expect_eof().unwrap();
+    /// assert!(input2.is_empty());
+    /// ```
+    pub fn into_inner_expect_eof(mut self) -> io::Result<R> {
+        self.expect_eof()?;
+        Ok(self.source)
+    }
+
+    /// Checks that the stream ends at the current position.
+    ///
+    /// If there are any bytes left after the current position, either in the internal buffer
+    /// or in the underlying stream, an error of kind `InvalidData` is returned. Note that
+    /// checking the underlying stream may require reading one more byte from it, but the
+    /// current position is never changed, so the trailing data can still be read afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let mut buf = [0; 2];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert!(ar.expect_eof().is_err());
+    ///
+    /// ar.read_exact(&mut buf[..1]).unwrap();
+    /// assert!(ar.expect_eof().is_ok());
+    /// ```
+    pub fn expect_eof(&mut self) -> io::Result<()> {
+        if self.pos < self.buf.len() || self.read_up_to(1)? > 0 {
+            Err(io::Error::new(io::ErrorKind::InvalidData, "trailing unconsumed data"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the data which has been buffered after the current position, that is,
+    /// the part of the buffer which has not been read yet.
+    ///
+    /// This never reads from the underlying stream. To view the whole accumulated data,
+    /// including the bytes which were already read, use the `AsRef<[u8]>` implementation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufRead;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    /// assert_eq!(ar.buffer(), &[]);
+    ///
+    /// ar.fill_buf().unwrap();
+    /// ar.consume(1);
+    /// assert_eq!(ar.buffer(), &[2, 3]);
+    /// ```
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf.as_slice()[self.pos..]
+    }
+
+    /// Returns the data which is already in memory ahead of the current position, without
+    /// ever reading from the underlying stream.
+    ///
+    /// **This is not the same as `fill_buf()`**: `fill_buf()` reads more data from the
+    /// underlying stream if nothing is buffered after the current position, so it may block
+    /// or fail, and it takes `&mut self`. This method takes `&self`, never reads anything
+    /// and may return an empty slice even if the stream has more data. It is the same as
+    /// `buffer()`, under a name which makes the difference explicit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufRead;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    /// assert_eq!(ar.peek_buffered(), &[]);
+    /// assert_eq!(ar.fill_buf().unwrap(), &[1, 2, 3]);
+    /// assert_eq!(ar.peek_buffered(), &[1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn peek_buffered(&self) -> &[u8] {
+        self.buffer()
+    }
+
+    /// Returns a cursor over the whole buffered data, positioned at the current position.
+    ///
+    /// The cursor is an independent view: reading and seeking it does not affect this reader,
+    /// which makes it convenient for speculative parsing. It only covers the data which is
+    /// already buffered and never reads from the underlying stream, so it may end before
+    /// the stream does. Cursor positions are offsets in the buffer; they differ from
+    /// the positions of this reader if the beginning of the stream has been discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{BufRead, Read};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4];
+    /// let mut ar = AccReader::new(input);
+    /// ar.fill_buf().unwrap();
+    /// ar.consume(1);
+    ///
+    /// let mut rest = Vec::new();
+    /// ar.as_cursor().read_to_end(&mut rest).unwrap();
+    /// assert_eq!(rest, [2, 3, 4]);
+    /// assert_eq!(ar.buffer(), &[2, 3, 4]);
+    /// ```
+    #[inline]
+    pub fn as_cursor(&self) -> io::Cursor<&[u8]> {
+        let mut cursor = io::Cursor::new(self.buf.as_slice());
+        cursor.set_position(self.pos as u64);
+        cursor
+    }
+
+    /// Returns the number of bytes before the current position which are still retained in
+    /// the internal buffer, that is, how far back it is possible to seek.
+    ///
+    /// Without a rewind window this is the same as the current position, unless the data was
+    /// discarded explicitly, e.g. with `clear()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReaderBuilder;
+    ///
+    /// let input: &[u8] = &[0; 100];
+    /// let mut ar = AccReaderBuilder::new().rewind_window(Some(10)).build(input);
+    ///
+    /// ar.seek(SeekFrom::Start(5)).unwrap();
+    /// assert_eq!(ar.rewind_available(), 5);
+    ///
+    /// ar.seek(SeekFrom::Start(50)).unwrap();
+    /// assert!(ar.rewind_available() >= 10);
+    /// let back = ar.rewind_available() as i64;
+    /// assert!(ar.seek(SeekFrom::Current(-back)).is_ok());
+    /// ```
+    #[inline]
+    pub fn rewind_available(&self) -> u64 {
+        self.pos as u64
+    }
+
+    /// Returns the number of bytes after the current position which are already buffered
+    /// and can be read without reading from the underlying stream.
+    ///
+    /// This is the same as the length of the slice returned by `buffer()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufRead;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// ar.fill_buf().unwrap();
+    /// ar.consume(1);
+    /// assert_eq!(ar.forward_buffered(), 2);
+    /// ```
+    #[inline]
+    pub fn forward_buffered(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    /// Returns the absolute offset in the underlying stream of the first byte of the internal
+    /// buffer.
+    ///
+    /// The data retained in the internal buffer, which is available through `as_ref()`, does
+    /// not start at the beginning of the stream once some of it is evicted, e.g. with a rewind
+    /// window or `drop_consumed()`. Adding the returned offset to an index into that slice
+    /// gives the absolute offset of the byte in the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let mut buf = [0; 3];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// ar.drop_consumed();
+    /// assert_eq!(ar.buffer_start_offset(), 3);
+    /// ```
+    #[inline]
+    pub fn buffer_start_offset(&self) -> u64 {
+        self.base
+    }
+
+    /// Returns the absolute offset in the underlying stream up to which it has been read
+    /// into the internal buffer.
+    ///
+    /// This is where the next read from the underlying stream starts, which is usually ahead
+    /// of the current position, since the reader buffers data in advance. Knowing it is useful
+    /// when coordinating with another reader of the same resource. Bytes inserted with
+    /// `push_front()` are counted as if they came from the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufRead;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::with_increment(4, input);
+    ///
+    /// ar.fill_buf().unwrap();
+    /// ar.consume(1);
+    /// assert_eq!(ar.position(), 1);
+    /// assert_eq!(ar.source_read_offset(), 4);
+    /// ```
+    #[inline]
+    pub fn source_read_offset(&self) -> u64 {
+        self.buffered_end()
+    }
+
+    /// Marks up to `amt` buffered bytes after the current position as read, returning
+    /// the number of bytes actually consumed.
+    ///
+    /// Unlike `BufRead::consume()`, consuming more than is buffered is not a logic error:
+    /// the position is moved to the end of the buffered data, and the returned count shows
+    /// how far it has moved. The underlying stream is never read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufRead;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::with_increment(3, input);
+    ///
+    /// ar.fill_buf().unwrap();
+    /// assert_eq!(ar.consume_up_to(2), 2);
+    /// assert_eq!(ar.consume_up_to(10), 1);
+    /// assert_eq!(ar.consume_up_to(10), 0);
+    /// ```
+    pub fn consume_up_to(&mut self, amt: usize) -> usize {
+        let n = cmp::min(amt, self.buf.len() - self.pos);
+        BufRead::consume(self, n);
+        self.debug_check_invariants();
+        n
+    }
+
+    /// Removes the buffered data after the current position from the internal buffer and
+    /// returns it.
+    ///
+    /// This hands the bytes returned by `buffer()` over to another consumer without reading
+    /// them through this reader. Afterwards the current position is at the end of the buffer,
+    /// and subsequent reads continue from the underlying stream. Note that the transferred
+    /// bytes are no longer part of the accumulated data, so it becomes impossible to seek
+    /// back over them: the stream offsets of the data read later are shifted accordingly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{BufRead, Read};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::with_increment(3, input);
+    ///
+    /// ar.fill_buf().unwrap();
+    /// ar.consume(1);
+    /// assert_eq!(ar.take_buffered(), [2, 3]);
+    ///
+    /// let mut rest = Vec::new();
+    /// ar.read_to_end(&mut rest).unwrap();
+    /// assert_eq!(rest, [4, 5]);
+    /// assert_eq!(ar.as_ref(), &[1, 4, 5]);
+    /// ```
+    pub fn take_buffered(&mut self) -> Vec<u8> {
+        let taken = self.buf.as_slice()[self.pos..].to_vec();
+        self.buf.truncate(self.pos);
+        self.truncate_line_index();
+
+        // the taken data is removed from the stream offsets
+        if let Some(ref mut end) = self.logical_end {
+            *end -= taken.len() as u64;
+        }
+        if let Some(ref mut known_len) = self.known_len {
+            *known_len = known_len.saturating_sub(taken.len() as u64);
+        }
+        self.debug_check_invariants();
+        taken
+    }
+
+    /// Inserts the provided bytes at the current position, so that they are read next,
+    /// before the rest of the buffered data and of the underlying stream.
+    ///
+    /// This "unreads" data which was obtained elsewhere, e.g. bytes peeked from a socket before
+    /// it was wrapped. The inserted bytes become a part of the accumulated data like any other:
+    /// the offsets of everything after the current position, including the end set with
+    /// `truncate_at()` and the length declared with `with_known_length()`, are shifted by
+    /// their length. They are not reported to the function set with `on_source_read()` and not
+    /// counted towards the read budget, and the buffer size limit is not checked, so
+    /// the buffer may exceed it until the inserted data is discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[3, 4];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// ar.push_front(&[1, 2]);
+    /// let mut data = Vec::new();
+    /// ar.read_to_end(&mut data).unwrap();
+    /// assert_eq!(data, [1, 2, 3, 4]);
+    /// ```
+    pub fn push_front(&mut self, data: &[u8]) {
+        let position = self.absolute_position();
+        let len = data.len() as u64;
+
+        let tail = self.buf.as_slice()[self.pos..].to_vec();
+        self.buf.truncate(self.pos);
+        self.buf.extend_from_slice(data);
+        self.buf.extend_from_slice(&tail);
+        self.track_peak();
+
+        if let Some(ref mut end) = self.logical_end {
+            *end += len;
+        }
+        if let Some(ref mut known_len) = self.known_len {
+            *known_len += len;
+        }
+        if let Some(ref mut starts) = self.line_starts {
+            let i = starts.iter().position(|&start| start > position).unwrap_or(starts.len());
+            for start in &mut starts[i..] {
+                *start += len;
+            }
+            let new_starts = data.iter().enumerate()
+                .filter(|&(_, &b)| b == b'\n')
+                .map(|(j, _)| position + j as u64 + 1);
+            let rest = starts.split_off(i);
+            starts.extend(new_starts);
+            starts.extend(rest);
+        }
+        self.debug_check_invariants();
+    }
+
+    /// Returns `true` if the end of the underlying stream, or the end set with `truncate_at()`,
+    /// has been reached.
+    ///
+    /// Once the whole underlying stream is buffered, for example after seeking with
+    /// `SeekFrom::End` or reading everything with `read_to_end()`, no further operation will
+    /// read from it, so seeking anywhere within the stream is cheap and never blocks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    /// assert!(!ar.is_fully_buffered());
+    ///
+    /// ar.seek(SeekFrom::End(0)).unwrap();
+    /// assert!(ar.is_fully_buffered());
+    /// ```
+    #[inline]
+    pub fn is_fully_buffered(&self) -> bool {
+        self.eof || self.logical_end.is_some_and(|end| self.buffered_end() >= end)
+    }
+
+    /// Reads the whole underlying stream into the internal buffer and returns its length.
+    ///
+    /// This is the recommended first pass when parsing formats which are read from the end,
+    /// like ZIP archives whose central directory is located at the end of the file, from
+    /// a non-seekable stream like a pipe. Seeking with `SeekFrom::End` would buffer the whole
+    /// stream anyway, but calling this method up front makes it explicit and makes the point
+    /// where the stream is read and where the I/O errors can happen predictable. Afterwards,
+    /// all seeks, including the ones using `SeekFrom::End`, are served from the buffer: they
+    /// never read from the underlying stream and therefore never block. The returned length
+    /// is relative to the origin, the same as the position `SeekFrom::End(0)` would move to.
+    /// The current position is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// assert_eq!(ar.buffer_fully().unwrap(), 5);
+    /// assert!(ar.is_fully_buffered());
+    ///
+    /// // e.g. read the trailer first, then the data it refers to
+    /// let mut buf = [0];
+    /// ar.seek(SeekFrom::End(-1)).unwrap();
+    /// ar.read_exact(&mut buf).unwrap();
+    /// ar.seek(SeekFrom::Start(buf[0] as u64 - 1)).unwrap();
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert_eq!(buf, [5]);
+    /// ```
+    pub fn buffer_fully(&mut self) -> io::Result<u64> {
+        self.fetch_to_end()?;
+        Ok(self.buffered_end().saturating_sub(self.origin))
+    }
+
+    /// Checks the underlying stream for data appended after its end was reached, reads all of
+    /// it into the internal buffer and returns the new length of the stream.
+    ///
+    /// Once the underlying stream has reported its end, e.g. after seeking with
+    /// `SeekFrom::End`, the reader does not read from it again for seeking. For a stream which
+    /// keeps growing, like a file which is still being written, this method forgets about
+    /// the end and reads everything which is available now, so it can be polled in
+    /// a `tail -f`-style loop. The returned length is relative to the origin, the same as
+    /// the position `SeekFrom::End(0)` would move to. The current position is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// assert_eq!(ar.seek(SeekFrom::End(0)).unwrap(), 3);
+    /// assert_eq!(ar.refresh_end().unwrap(), 3);
+    /// ```
+    pub fn refresh_end(&mut self) -> io::Result<u64> {
+        self.eof = false;
+        self.fetch_to_end()?;
+        self.debug_check_invariants();
+        Ok(self.buffered_end().saturating_sub(self.origin))
+    }
+
+    /// Reads the rest of the underlying stream and throws it away, returning the number of
+    /// bytes discarded.
+    ///
+    /// This is for the cases when the rest of the stream is not needed but has to be consumed,
+    /// e.g. to reuse a connection. Unlike reading or seeking to the end, the data is read
+    /// in chunks of the buffer increment and is not retained, so the memory usage does not
+    /// grow. The current position is moved to the end of the data buffered before the call,
+    /// which is still available for seeking. The discarded data is not a part of
+    /// the accumulated data, so afterwards the stream ends where the buffered data ends, and it is
+    /// impossible to seek past that point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{BufRead, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::with_increment(2, input);
+    ///
+    /// ar.fill_buf().unwrap();
+    /// assert_eq!(ar.discard_to_end().unwrap(), 3);
+    /// assert_eq!(ar.seek(SeekFrom::End(0)).unwrap(), 2);
+    /// ```
+    pub fn discard_to_end(&mut self) -> io::Result<u64> {
+        self.pos = self.buf.len();
+
+        let mut discarded = 0;
+        loop {
+            let n = self.fill_size();
+            let read = match self.fetch(n) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            // the discarded data is removed from the stream offsets
+            let len = self.buf.len() - read;
+            self.buf.truncate(len);
+            self.pos = len;
+            self.truncate_line_index();
+            if let Some(ref mut end) = self.logical_end {
+                *end -= read as u64;
+            }
+            if let Some(ref mut known_len) = self.known_len {
+                *known_len = known_len.saturating_sub(read as u64);
+            }
+            discarded += read as u64;
+        }
+        self.debug_check_invariants();
+        Ok(discarded)
+    }
+
+    /// Returns the number of bytes left in the stream after the current position.
+    ///
+    /// This reads the whole rest of the underlying stream into the internal buffer, just like
+    /// seeking with `SeekFrom::End` does, so it will hang on infinite streams or fill up all
+    /// of the available memory. The current position is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let mut buf = [0];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert_eq!(ar.bytes_until_eof().unwrap(), 2);
+    /// ```
+    pub fn bytes_until_eof(&mut self) -> io::Result<u64> {
+        self.fetch_to_end()?;
+        Ok(self.buffered_end() - self.absolute_position())
+    }
+
+    /// Returns the absolute offset at which the specified line starts, counting from zero,
+    /// if the line index is enabled and the line has already been buffered.
+    ///
+    /// This never reads from the underlying stream. If the stream ends with a newline,
+    /// the end of the stream is reported as the start of an empty line after it.
+    /// See `with_line_index()` for more information.
+    #[inline]
+    pub fn line_offset(&self, line: usize) -> Option<u64> {
+        self.line_starts.as_ref().and_then(|starts| starts.get(line).cloned())
+    }
+
+    /// Moves the current position to the start of the specified line, counting from zero,
+    /// and returns the absolute offset of the line.
+    ///
+    /// The underlying stream is read until the line is found. If the stream has fewer lines,
+    /// `SeekError::BeyondEnd` is returned; if the line index is not enabled with
+    /// `with_line_index()`, an error of kind `InvalidInput` is returned.
+    /// The current position is not changed if an error is returned.
+    pub fn seek_to_line(&mut self, line: usize) -> io::Result<u64> {
+        if self.line_starts.is_none() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "line index is not enabled"));
+        }
+
+        loop {
+            if let Some(offset) = self.line_offset(line) {
+                return self.seek_to(offset);
+            }
+
+            let n = self.fill_size();
+            match self.fetch(n) {
+                Ok(0) => return Err(SeekError::BeyondEnd.into()),
+                Ok(_) => {}
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Searches the stream after the current position for the provided byte sequence,
+    /// returning the absolute offset of its first occurrence, or `None` if the stream ends
+    /// before it is found.
+    ///
+    /// The underlying stream is read by the buffer increment until the sequence is found,
+    /// and only the newly read data, together with the last `needle.len() - 1` bytes before it,
+    /// is searched after each read, so occurrences spanning several reads are found too.
+    /// The current position is not changed, so the found occurrence can be read after seeking
+    /// to the returned offset. An empty `needle` is found at the current position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = b"header\r\nbody";
+    /// let mut ar = AccReader::with_increment(4, input);
+    ///
+    /// assert_eq!(ar.scan_for(b"\r\n").unwrap(), Some(6));
+    /// assert_eq!(ar.scan_for(b"\n\n").unwrap(), None);
+    ///
+    /// ar.seek(SeekFrom::Start(8)).unwrap();
+    /// let mut body = String::new();
+    /// ar.read_to_string(&mut body).unwrap();
+    /// assert_eq!(body, "body");
+    /// ```
+    pub fn scan_for(&mut self, needle: &[u8]) -> io::Result<Option<u64>> {
+        if needle.is_empty() {
+            return Ok(Some(self.absolute_position()));
+        }
+
+        // absolute offset from which the buffer has not been searched yet
+        let mut from = self.absolute_position();
+        loop {
+            let start = (from - self.base) as usize;
+            let data = &self.buf.as_slice()[start..];
+            if let Some(i) = data.windows(needle.len()).position(|w| w == needle) {
+                return Ok(Some(from + i as u64));
+            }
+            from += data.len().saturating_sub(needle.len() - 1) as u64;
+
+            let n = self.fill_size();
+            match self.fetch(n) {
+                Ok(0) => return Ok(None),
+                Ok(_) => {}
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Cuts the stream off at the specified offset, so that it appears to end there even
+    /// if the underlying stream contains more data.
+    ///
+    /// Reads stop at the new end just like they would at the real end of the stream, and
+    /// seeking beyond it fails with the same error. No more data is read from the underlying
+    /// stream once the new end is reached. Buffered data after the new end is discarded, and
+    /// if the current position is after the new end, it is moved to the new end.
+    ///
+    /// The end can only be moved backwards: if the stream has already been truncated at
+    /// an earlier offset, this does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::new(input);
+    /// ar.truncate_at(3);
+    ///
+    /// let mut data = Vec::new();
+    /// ar.read_to_end(&mut data).unwrap();
+    /// assert_eq!(data, [1, 2, 3]);
+    /// assert!(ar.seek(SeekFrom::Start(4)).is_err());
+    /// ```
+    pub fn truncate_at(&mut self, end: u64) {
+        let end = match self.logical_end {
+            Some(logical_end) => cmp::min(logical_end, end),
+            None => end,
+        };
+        if end < self.buffered_end() {
+            let len = end.saturating_sub(self.base) as usize;
+            self.buf.truncate(len);
+            self.pos = cmp::min(self.pos, len);
+            self.truncate_line_index();
+        }
+        self.logical_end = Some(end);
+        self.debug_check_invariants();
+    }
+
+    /// Cuts the stream off at the current position.
+    ///
+    /// This is the same as `truncate_at()` with the current position as the argument.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{BufRead, Read};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// ar.fill_buf().unwrap();
+    /// ar.consume(2);
+    /// ar.truncate_at_current();
+    ///
+    /// let mut buf = [0];
+    /// assert_eq!(ar.read(&mut buf).unwrap(), 0);
+    /// assert_eq!(ar.as_ref(), &[1, 2]);
+    /// ```
+    #[inline]
+    pub fn truncate_at_current(&mut self) {
+        let position = self.absolute_position();
+        self.truncate_at(position);
+    }
+
+    /// Moves the current position back to where it was when `fill_buf()` was last called,
+    /// returning the new position.
+    ///
+    /// This allows re-scanning the data returned by the last `fill_buf()` call after some of it
+    /// was consumed, e.g. to parse a record again. If `fill_buf()` has not been called yet,
+    /// the position is moved to the beginning of the stream. An error is returned only if
+    /// the data at that position has been discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufRead;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// ar.fill_buf().unwrap();
+    /// ar.consume(2);
+    /// assert_eq!(ar.rewind_to_last_fill().unwrap(), 0);
+    /// assert_eq!(ar.fill_buf().unwrap(), &[1, 2, 3]);
+    /// ```
+    pub fn rewind_to_last_fill(&mut self) -> io::Result<u64> {
+        let last_fill = self.last_fill;
+        let result = self.seek_to(last_fill);
+        self.debug_check_invariants();
+        result
+    }
+
+    /// Marks the current position, so that it can be returned to with `reset_to_mark()`.
+    ///
+    /// This mirrors `mark()` of Java's `InputStream`: the mark is invalidated once more than
+    /// `readlimit` bytes have been read past it. Unlike in Java, the limit does not affect how
+    /// much data is buffered, because the accumulated data is retained anyway. Calling this
+    /// method again replaces the previous mark.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let mut buf = [0; 2];
+    /// ar.mark(2);
+    /// ar.read_exact(&mut buf).unwrap();
+    /// ar.reset_to_mark().unwrap();
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert_eq!(buf, [1, 2]);
+    ///
+    /// ar.read_exact(&mut buf[..1]).unwrap();
+    /// assert!(ar.reset_to_mark().is_err());
+    /// ```
+    #[inline]
+    pub fn mark(&mut self, readlimit: usize) {
+        self.mark = Some((self.absolute_position(), readlimit));
+    }
+
+    /// Moves the current position back to the position recorded by `mark()`.
+    ///
+    /// Returns an error of kind `InvalidInput` if no mark has been set, or if more than
+    /// the read limit of the mark has been read past it since the mark was set, in which case
+    /// the mark is also discarded. The mark stays in place after a successful reset, so it can be
+    /// returned to several times.
+    pub fn reset_to_mark(&mut self) -> io::Result<()> {
+        match self.mark {
+            Some((mark, readlimit)) if self.absolute_position().saturating_sub(mark) <= readlimit as u64 => {
+                self.seek_to(mark).map(|_| ())
+            }
+            Some(_) => {
+                self.mark = None;
+                Err(io::Error::new(io::ErrorKind::InvalidInput, "mark has been invalidated"))
+            }
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "no mark has been set")),
+        }
+    }
+
+    /// Moves the current position to the beginning of the accumulated data.
+    ///
+    /// This is a cheap rewind which never fails: the data is replayed from the internal
+    /// buffer. It is the same as seeking to the beginning of the stream, unless some data
+    /// has already been discarded because of the rewind window.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let mut buf = [0; 2];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// ar.reset();
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert_eq!(buf, [1, 2]);
+    /// ```
+    #[inline]
+    pub fn reset(&mut self) {
+        self.pos = 0;
+        self.debug_check_invariants();
+    }
+
+    /// Returns the current position in the stream, relative to the origin set with
+    /// `set_origin()`.
+    ///
+    /// This is the same as `Seek::stream_position()`, but does not require a mutable reference
+    /// and cannot fail, mirroring `std::io::Cursor::position()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let mut buf = [0; 2];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert_eq!(ar.position(), 2);
+    /// ```
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.absolute_position().saturating_sub(self.origin)
+    }
+
+    /// Moves the current position to the specified offset relative to the origin, mirroring
+    /// `std::io::Cursor::set_position()`.
+    ///
+    /// Unlike seeking with `SeekFrom::Start`, this never fails because of the offset itself:
+    /// the position is clamped into the available data instead. Offsets beyond the end of
+    /// the stream move the position to the end, so subsequent reads return no data, as they
+    /// would with a `Cursor`; offsets before the data retained by the rewind window move
+    /// the position to the beginning of that data. The underlying stream is read as needed
+    /// to reach the offset, and only the errors from reading it are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// ar.set_position(1).unwrap();
+    /// let mut buf = [0];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert_eq!(buf, [2]);
+    ///
+    /// ar.set_position(10).unwrap();
+    /// assert_eq!(ar.position(), 3);
+    /// assert_eq!(ar.read(&mut buf).unwrap(), 0);
+    /// ```
+    pub fn set_position(&mut self, pos: u64) -> io::Result<()> {
+        let pos = self.origin.saturating_add(pos);
+        if pos > self.buffered_end() {
+            let need_to_read = pos - self.buffered_end();
+            self.read_up_to(need_to_read)?;
+        }
+        let target = cmp::max(cmp::min(pos, self.buffered_end()), self.base);
+        self.pos = (target - self.base) as usize;
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    /// Moves the current position to the specified absolute offset without any checks.
+    ///
+    /// This is an escape hatch for hot loops which seek a lot within data which is known to be
+    /// buffered, e.g. after `ensure_buffered()`: unlike `seek()` or `set_position()`, it never
+    /// reads from the underlying stream and does not validate the offset in release builds.
+    ///
+    /// # Safety
+    ///
+    /// `abs_pos` must be within the buffered data, that is, between `buffer_start_offset()`
+    /// and `source_read_offset()`, inclusive. Other methods of this reader rely on
+    /// the current position being inside the buffer, so if this is violated, their results
+    /// are unspecified: they may return wrong data or panic. This is not memory-unsafe,
+    /// because all accesses to the buffer are still bounds-checked. In debug builds,
+    /// an invalid offset causes a panic right away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// ar.ensure_buffered(5).unwrap();
+    /// let mut buf = [0; 2];
+    /// for &offset in &[3, 1] {
+    ///     unsafe { ar.set_position_unchecked(offset); }
+    ///     ar.read_exact(&mut buf).unwrap();
+    /// }
+    /// assert_eq!(buf, [2, 3]);
+    /// ```
+    #[inline]
+    pub unsafe fn set_position_unchecked(&mut self, abs_pos: u64) {
+        debug_assert!(abs_pos >= self.base && abs_pos <= self.buffered_end(),
+                      "position {} is outside of the buffered data", abs_pos);
+        self.pos = abs_pos.wrapping_sub(self.base) as usize;
+    }
+
+    /// Sets the offset in the stream which positions are relative to.
+    ///
+    /// This is useful for parsing data embedded in a larger stream, with offsets relative
+    /// to the beginning of the embedded part: afterwards `SeekFrom::Start(n)` moves the position
+    /// to the absolute offset `origin + n`, and the positions returned by `Seek`, `position()`
+    /// and `set_position()` are relative to the origin as well. `SeekFrom::Current` still
+    /// moves the position relative to the current one, and `SeekFrom::End` relative to the end
+    /// of the stream, but seeking before the origin with them fails with
+    /// `SeekError::BeforeStart`. While the current position is before the origin,
+    /// it is reported as zero.
+    ///
+    /// The origin does not affect any data and the current position itself. The methods
+    /// documented as working with absolute offsets, like `scan_for()`, `truncate_at()`,
+    /// `ensure_buffered()`, `slice_range()`, `buffer_start_offset()` or
+    /// `source_read_offset()`, always count from the beginning of the underlying stream and
+    /// ignore the origin. The default origin is zero, the beginning of the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[0xff, 0xff, 1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// ar.set_origin(2);
+    /// assert_eq!(ar.seek(SeekFrom::Start(1)).unwrap(), 1);
+    ///
+    /// let mut buf = [0; 2];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert_eq!(buf, [2, 3]);
+    /// assert_eq!(ar.position(), 3);
+    /// ```
+    #[inline]
+    pub fn set_origin(&mut self, origin: u64) {
+        self.origin = origin;
+        self.debug_check_invariants();
+    }
+
+    /// Advances the current position by `n` bytes without copying them anywhere, returning
+    /// the number of bytes skipped.
+    ///
+    /// The skipped bytes are read from the underlying stream into the internal buffer as
+    /// usual, so it is possible to seek back to them later. Fewer than `n` bytes are skipped
+    /// only if the stream ends earlier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// assert_eq!(ar.skip(3).unwrap(), 3);
+    /// let mut buf = [0];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert_eq!(buf, [4]);
+    ///
+    /// assert_eq!(ar.skip(3).unwrap(), 0);
+    /// ```
+    pub fn skip(&mut self, n: u64) -> io::Result<u64> {
+        let start = self.absolute_position();
+        let target = start.saturating_add(n);
+        if target > self.buffered_end() {
+            let need_to_read = target - self.buffered_end();
+            self.read_up_to(need_to_read)?;
+        }
+        let target = cmp::min(target, self.buffered_end());
+        self.pos = (target - self.base) as usize;
+        self.debug_check_invariants();
+        Ok(target - start)
+    }
+
+    /// Discards all of the accumulated data.
+    ///
+    /// Afterwards the current position is at the end of what was read from the underlying
+    /// stream so far, and further reads return fresh data from it. Since the discarded data
+    /// cannot be read again, this only makes sense when the reader is used to keep reading
+    /// new data, e.g. to release memory after a chunk of the stream has been processed.
+    /// Positions stay absolute, so seeking to the discarded data fails with
+    /// `SeekError::Evicted`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{BufRead, Read};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4];
+    /// let mut ar = AccReader::with_increment(2, input);
+    ///
+    /// ar.fill_buf().unwrap();
+    /// ar.clear();
+    ///
+    /// let mut rest = Vec::new();
+    /// ar.read_to_end(&mut rest).unwrap();
+    /// assert_eq!(rest, [3, 4]);
+    /// ```
+    pub fn clear(&mut self) {
+        self.base = self.buffered_end();
+        self.buf.truncate(0);
+        self.pos = 0;
+        self.debug_check_invariants();
+    }
+
+    /// Discards the accumulated data before the current position.
+    ///
+    /// Unlike `clear()`, the data which has been buffered but not read yet is kept. This is
+    /// a one-shot alternative to `AccReaderBuilder::rewind_window()`. Positions stay absolute,
+    /// so `SeekFrom::Start` keeps referring to the same offsets in the stream, and seeking to
+    /// the discarded data fails with `SeekError::Evicted`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{BufRead, Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// ar.fill_buf().unwrap();
+    /// ar.consume(2);
+    /// ar.drop_consumed();
+    /// assert_eq!(ar.as_ref(), &[3, 4]);
+    ///
+    /// ar.seek(SeekFrom::Start(3)).unwrap();
+    /// let mut rest = Vec::new();
+    /// ar.read_to_end(&mut rest).unwrap();
+    /// assert_eq!(rest, [4]);
+    /// ```
+    pub fn drop_consumed(&mut self) {
+        self.buf.drain_front(self.pos);
+        self.base += self.pos as u64;
+        self.pos = 0;
+        self.debug_check_invariants();
+    }
+
+    /// Checks if there is any data left to read after the current position.
+    ///
+    /// This mirrors the unstable `BufRead::has_data_left()`. If the internal buffer has no
+    /// data after the current position, this calls `fill_buf()`, so the underlying stream
+    /// may be read, but the current position is never changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1];
+    /// let mut ar = AccReader::new(input);
+    /// assert!(ar.has_data_left().unwrap());
+    ///
+    /// let mut buf = [0];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert!(!ar.has_data_left().unwrap());
+    /// ```
+    pub fn has_data_left(&mut self) -> io::Result<bool> {
+        self.fill_buf().map(|b| !b.is_empty())
+    }
+
+    /// Checks if the stream contains no data at all.
+    ///
+    /// Unlike `has_data_left()`, this looks at the whole stream and not only at the part after
+    /// the current position. If anything has already been read from the underlying stream,
+    /// this returns `Ok(false)` immediately; otherwise, the stream is read once, like
+    /// `fill_buf()` does, to find out whether it has any data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[];
+    /// assert!(AccReader::new(input).is_empty().unwrap());
+    ///
+    /// let input: &[u8] = &[1];
+    /// assert!(!AccReader::new(input).is_empty().unwrap());
+    /// ```
+    pub fn is_empty(&mut self) -> io::Result<bool> {
+        if self.buffered_end() > 0 {
+            return Ok(false);
+        }
+        // same as fill_buf(), but at least one byte is needed to decide
+        let n = match self.max_lookahead {
+            Some(max_lookahead) => cmp::min(self.fill_size(), max_lookahead),
+            None => self.fill_size(),
+        };
+        self.fetch(cmp::max(n, 1)).map(|read| read == 0)
+    }
+
+    /// Returns the number of bytes the internal buffer can hold without reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let ar = AccReader::with_initial_capacity(512, input);
+    /// assert!(ar.capacity() >= 512);
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Reserves capacity for at least `additional` more bytes in the internal buffer.
+    ///
+    /// This does not read any data from the underlying stream, it only grows the buffer
+    /// to avoid repeated reallocations while the data is accumulated, which is useful when
+    /// the approximate size of the stream becomes known after the reader was created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::with_initial_capacity(0, input);
+    ///
+    /// ar.reserve(65536);
+    /// assert!(ar.capacity() >= 65536);
+    /// ```
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        let old_capacity = self.buf.capacity();
+        self.buf.reserve(additional);
+        self.track_realloc(old_capacity);
+    }
+
+    /// Makes sure the internal buffer can hold `expected_total` bytes of the stream without
+    /// reallocating.
+    ///
+    /// Unlike `reserve()`, which counts from the amount of data already buffered, this takes
+    /// the expected total amount of buffered data, which is convenient when the size of the
+    /// stream is known in advance: calling it before seeking to some offset and reading
+    /// the data up to the expected end makes all of that happen with a single allocation.
+    /// Reading past the expected end, even if only to find out that the stream has ended
+    /// as `read_to_end()` does, may still grow the buffer. Nothing is done if the buffer is
+    /// already large enough.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[0; 1000];
+    /// let mut ar = AccReader::with_initial_capacity(0, input);
+    /// ar.seek_prealloc(1000);
+    ///
+    /// ar.seek(SeekFrom::Start(500)).unwrap();
+    /// let mut rest = [0; 500];
+    /// ar.read_exact(&mut rest).unwrap();
+    /// assert_eq!(ar.realloc_count(), 1);
+    /// ```
+    pub fn seek_prealloc(&mut self, expected_total: usize) {
+        let additional = expected_total.saturating_sub(self.buf.len());
+        if self.buf.capacity() < self.buf.len() + additional {
+            self.reserve(additional);
+        }
+        self.debug_check_invariants();
+    }
+
+    /// Returns the number of times the internal buffer has grown its capacity, that is,
+    /// the number of reallocations performed while accumulating the data.
+    ///
+    /// This is a diagnostic which helps choosing the initial capacity and the increment.
+    /// The allocation of the initial capacity is not counted. Growth is detected through
+    /// `Buffer::capacity()`, so with custom buffers which do not report their capacity
+    /// every operation which appends data is counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[0; 64];
+    /// let mut ar = AccReader::with_initial_capacity(64, input);
+    ///
+    /// let mut buf = [0; 64];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert_eq!(ar.realloc_count(), 0);
+    /// ```
+    #[inline]
+    pub fn realloc_count(&self) -> u64 {
+        self.reallocs
+    }
+
+    /// Returns the maximum number of bytes the internal buffer has ever held.
+    ///
+    /// This is a diagnostic which helps choosing the limits on the buffer size. When data is
+    /// discarded from the front of the buffer, e.g. with a rewind window or `drop_consumed()`,
+    /// the current size of the buffer does not show how much memory was needed, but this
+    /// high-water mark does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[0; 64];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let mut buf = [0; 64];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// ar.drop_consumed();
+    /// assert_eq!(ar.as_ref().len(), 0);
+    /// assert_eq!(ar.peak_buffered(), 64);
+    /// ```
+    #[inline]
+    pub fn peak_buffered(&self) -> usize {
+        self.peak_len
+    }
+
+    /// Writes all of the data from the current position to the end of the stream into the
+    /// provided writer, returning the number of bytes written.
+    ///
+    /// The data is read from the underlying stream and accumulated as usual, so once this
+    /// method returns, the stream is fully buffered and the current position is at its end;
+    /// seeking back allows processing the same data again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let mut copy = Vec::new();
+    /// assert_eq!(ar.copy_to(&mut copy).unwrap(), 3);
+    /// assert_eq!(copy, [1, 2, 3]);
+    ///
+    /// ar.seek(SeekFrom::Start(0)).unwrap();
+    /// let mut again = Vec::new();
+    /// ar.read_to_end(&mut again).unwrap();
+    /// assert_eq!(again, copy);
+    /// ```
+    pub fn copy_to<W: Write>(&mut self, dst: &mut W) -> io::Result<u64> {
+        let mut copied = 0;
+        loop {
+            let available = self.buf.len() - self.pos;
+            if available > 0 {
+                dst.write_all(&self.buf.as_slice()[self.pos..])?;
+                self.pos += available;
+                copied += available as u64;
+            }
+
+            let n = self.fill_size();
+            match self.fetch(n) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        self.trim_to_soft_cap();
+        self.debug_check_invariants();
+        Ok(copied)
+    }
+
+    /// Works like `BufRead::fill_buf()`, but returns `Ok(None)` instead of an error
+    /// of kind `WouldBlock` when the underlying stream is non-blocking and has no data
+    /// available yet.
+    ///
+    /// This is convenient for polling in an event loop: `Ok(None)` means that the call should
+    /// be repeated when the stream becomes readable, while other errors are returned as is.
+    /// `Ok(Some(data))` with empty `data` still means the end of the stream. This method is
+    /// only available with the `std` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// assert_eq!(ar.try_fill_buf().unwrap(), Some(&[1, 2, 3][..]));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn try_fill_buf(&mut self) -> io::Result<Option<&[u8]>> {
+        match BufRead::fill_buf(self).map(|_| ()) {
+            Ok(()) => Ok(Some(&self.buf.as_slice()[self.pos..])),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Reads up to `n` more bytes from the underlying stream into the internal buffer,
+    /// returning the number of bytes actually read.
+    ///
+    /// The current position is not changed. Fewer than `n` bytes are read only if the stream
+    /// ends or an error occurs. An error is returned only if nothing could be read; otherwise
+    /// the bytes read before the error are reported as a short read, and the error, if it
+    /// persists, will be returned by the next call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// assert_eq!(ar.fill_more(2).unwrap(), 2);
+    /// assert_eq!(ar.buffer(), &[1, 2]);
+    /// assert_eq!(ar.fill_more(2).unwrap(), 1);
+    /// assert_eq!(ar.fill_more(2).unwrap(), 0);
+    /// ```
+    pub fn fill_more(&mut self, n: u64) -> io::Result<usize> {
+        match self.read_up_to_partial(n) {
+            (0, Err(e)) => Err(e),
+            (read, _) => Ok(read),
+        }
+    }
+
+    /// Reads from the underlying stream until the data up to the specified absolute offset
+    /// is buffered, or until the stream ends.
+    ///
+    /// This can be used to prefetch a region with as few reads as possible before doing
+    /// many small seeks and reads inside it. Unlike seeking, the current position is not
+    /// changed, and reaching the end of the stream before the offset is not an error: whatever
+    /// is available is buffered. If the maximum lookahead is set, nothing is buffered beyond
+    /// it, so fewer bytes may be buffered than requested.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// ar.ensure_buffered(4).unwrap();
+    /// assert_eq!(ar.buffer(), &[1, 2, 3, 4]);
+    ///
+    /// ar.ensure_buffered(10).unwrap();
+    /// assert_eq!(ar.buffer(), &[1, 2, 3, 4, 5]);
+    /// assert_eq!(ar.stream_position().unwrap(), 0);
+    /// ```
+    pub fn ensure_buffered(&mut self, abs_end: u64) -> io::Result<()> {
+        let abs_end = match self.max_lookahead {
+            Some(max_lookahead) => cmp::min(abs_end, self.absolute_position().saturating_add(max_lookahead as u64)),
+            None => abs_end,
+        };
+        let end = self.buffered_end();
+        if abs_end > end {
+            self.read_up_to(abs_end - end)?;
+        }
+        Ok(())
+    }
+
+    /// Seeks relative to the current position, like `Seek::seek()` with `SeekFrom::Current`.
+    ///
+    /// This mirrors `BufReader::seek_relative()`. Seeking backwards within the accumulated data
+    /// never touches the underlying stream, while seeking forwards past the buffered data
+    /// reads the missing bytes into the buffer. An error is returned if the target is before
+    /// the beginning of the stream or beyond its end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// ar.seek_relative(3).unwrap();
+    /// ar.seek_relative(-2).unwrap();
+    ///
+    /// let mut buf = [0];
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert_eq!(buf, [2]);
+    /// assert!(ar.seek_relative(-3).is_err());
+    /// ```
+    pub fn seek_relative(&mut self, offset: i64) -> io::Result<()> {
+        self.seek(SeekFrom::Current(offset)).map(|_| ())
+    }
+
+    /// Seeks within the buffered data only, never reading from the underlying stream.
+    ///
+    /// This works like `Seek::seek()`, except that `SeekFrom::End` is relative to the end of
+    /// the data buffered so far, and seeking beyond that fails with `SeekError::BeyondEnd`
+    /// instead of reading more data. This makes seeking predictable and non-blocking, e.g.
+    /// for parsers which manage buffering themselves through `fill_buf()`. The current
+    /// position is not changed if an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{BufRead, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::with_increment(3, input);
+    ///
+    /// ar.fill_buf().unwrap();
+    /// assert_eq!(ar.seek_buffered(SeekFrom::End(-1)).unwrap(), 2);
+    /// assert!(ar.seek_buffered(SeekFrom::Start(4)).is_err());
+    /// ```
+    pub fn seek_buffered(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let origin = self.origin;
+        let pos = match pos {
+            SeekFrom::Start(n) => SeekFrom::Start(origin.checked_add(n).ok_or(SeekError::BeyondEnd)?),
+            pos => pos,
+        };
+        // resolved in absolute offsets, because the current position may be before the origin
+        let target = resolve_buffered(pos, 0, self.absolute_position(), self.buffered_end())?;
+        if target < origin {
+            return Err(SeekError::BeforeStart.into());
+        } else if target < self.base {
+            return Err(SeekError::Evicted.into());
+        }
+        self.pos = (target - self.base) as usize;
+        self.debug_check_invariants();
+        Ok(target - origin)
+    }
+
+    /// Runs the provided closure with the stream positioned at the specified offset, restoring
+    /// the current position afterwards.
+    ///
+    /// The reader is seeked to `offset` as with `SeekFrom::Start(offset)`, and then `f` is
+    /// called with the reader, so it can read whatever it needs from there. When `f` returns,
+    /// the position is restored to what it was before this call, regardless of whether `f`
+    /// succeeded or not, and the result of `f` is returned. If seeking to `offset` fails,
+    /// `f` is not called and the error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let mut buf = [0];
+    /// ar.read_exact(&mut buf).unwrap();
+    ///
+    /// let tail = ar.at_offset(3, |ar| {
+    ///     let mut tail = Vec::new();
+    ///     ar.read_to_end(&mut tail).map(|_| tail)
+    /// }).unwrap();
+    /// assert_eq!(tail, [4, 5]);
+    ///
+    /// ar.read_exact(&mut buf).unwrap();
+    /// assert_eq!(buf, [2]);
+    /// ```
+    pub fn at_offset<T, F>(&mut self, offset: u64, f: F) -> io::Result<T>
+        where F: FnOnce(&mut Self) -> io::Result<T>
+    {
+        let saved_pos = self.absolute_position();
+        self.seek(SeekFrom::Start(offset))?;
+        let result = f(self);
+        // this can only fail if the saved position was discarded while running f
+        let restored = self.seek_to(saved_pos);
+        let value = result?;
+        restored?;
+        Ok(value)
+    }
+
+    /// Returns an iterator over chunks of the stream of the specified size, starting from
+    /// the current position.
+    ///
+    /// Each chunk contains `size` bytes, except for the last one, which may be shorter
+    /// if the stream ends. The iterator reads from this accumulating reader, so all of the
+    /// data it yields remains buffered; to restart the iteration from some other point,
+    /// seek the reader and create a new iterator. If reading fails after a part of a chunk has
+    /// been read, that shorter chunk is yielded first, and the error is yielded next.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let chunks: Vec<_> = ar.chunks(2).map(|c| c.unwrap()).collect();
+    /// assert_eq!(chunks, [vec![1, 2], vec![3, 4], vec![5]]);
+    ///
+    /// ar.seek(SeekFrom::Start(1)).unwrap();
+    /// let chunks: Vec<_> = ar.chunks(3).map(|c| c.unwrap()).collect();
+    /// assert_eq!(chunks, [vec![2, 3, 4], vec![5]]);
+    /// ```
+    #[inline]
+    pub fn chunks(&mut self, size: usize) -> Chunks<'_, R, B> {
+        assert!(size != 0, "chunk size must be non-zero");
+        Chunks { reader: self, size, error: None }
+    }
+
+    /// Returns a read-only cursor over the accumulated data, positioned at the specified
+    /// absolute offset.
+    ///
+    /// The stream is read up to the offset if necessary, with the same errors as
+    /// `seek()` with `SeekFrom::Start` would return, but the current position of this reader
+    /// is not changed. The cursor implements `Read`, `BufRead` and `Seek` over the data
+    /// which is buffered at the moment of its creation, using the same offsets as this reader;
+    /// it never reads from the underlying stream, so the end of the buffered data looks like
+    /// the end of the stream to it.
+    ///
+    /// Cursors are cheap to clone, and all of their clones can be moved independently, which
+    /// allows reading several parts of the stream in an interleaved fashion. Make sure that
+    /// all of the necessary data is buffered before creating the first cursor, e.g. by creating
+    /// it at the furthest offset and seeking it back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let mut c1 = ar.cursor_at(5).unwrap();
+    /// let mut c2 = c1.clone();
+    /// c1.seek(SeekFrom::Start(1)).unwrap();
+    /// c2.seek(SeekFrom::Start(3)).unwrap();
+    ///
+    /// let mut buf = [0; 2];
+    /// c1.read_exact(&mut buf).unwrap();
+    /// assert_eq!(buf, [2, 3]);
+    /// c2.read_exact(&mut buf).unwrap();
+    /// assert_eq!(buf, [4, 5]);
+    /// assert_eq!(c2.read(&mut buf).unwrap(), 0);
+    /// ```
+    pub fn cursor_at(&mut self, pos: u64) -> io::Result<BufferCursor<'_>> {
+        self.buffer_up_to(pos)?;
+        if pos < self.base {  // reading could have discarded the target
+            return Err(SeekError::Evicted.into());
+        }
+        Ok(BufferCursor {
+            data: self.buf.as_slice(),
+            base: self.base,
+            pos: (pos - self.base) as usize,
+        })
+    }
+
+    /// Returns a copy of the data between the specified absolute offsets, `start` inclusive
+    /// and `end` exclusive.
+    ///
+    /// The stream is read up to `end` if necessary, with the same errors as `seek()` with
+    /// `SeekFrom::Start` would return for it, so an error of kind `UnexpectedEof` is returned
+    /// if the range extends beyond the end of the stream. An error of kind `InvalidInput`
+    /// is returned if `start` is greater than `end`, or if the beginning of the range has been
+    /// discarded from the buffer. The current position is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// assert_eq!(ar.slice_range(1, 4).unwrap(), [2, 3, 4]);
+    /// assert!(ar.slice_range(3, 6).is_err());
+    /// assert_eq!(ar.buffer(), &[1, 2, 3, 4, 5]);
+    /// ```
+    pub fn slice_range(&mut self, start: u64, end: u64) -> io::Result<Vec<u8>> {
+        if start > end {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "range start is greater than its end"));
+        }
+        self.buffer_up_to(end)?;
+        if start < self.base {  // reading could have discarded the beginning
+            return Err(SeekError::Evicted.into());
+        }
+        let from = (start - self.base) as usize;
+        let to = (end - self.base) as usize;
+        Ok(self.buf.as_slice()[from..to].to_vec())
+    }
+
+    /// Returns an iterator over the lines of the stream, starting from the current position,
+    /// which also yields the offset of the beginning of each line.
+    ///
+    /// Lines are split and stripped of line endings in the same way as `BufRead::lines()`
+    /// does it. Since all of the data stays buffered, a previously seen line can be read
+    /// again by seeking to its offset and creating a new iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = b"first\nsecond\nthird\n";
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let lines: Vec<_> = ar.lines_seekable().map(|l| l.unwrap()).collect();
+    /// assert_eq!(lines[1], (6, "second".to_owned()));
+    ///
+    /// ar.seek(SeekFrom::Start(lines[1].0)).unwrap();
+    /// let (_, line) = ar.lines_seekable().next().unwrap().unwrap();
+    /// assert_eq!(line, "second");
+    /// ```
+    #[inline]
+    pub fn lines_seekable(&mut self) -> SeekableLines<'_, R, B> {
+        SeekableLines { reader: self }
+    }
+
+    /// Returns an iterator over the records of the stream separated by the provided byte
+    /// sequence, starting from the current position.
+    ///
+    /// Separators are found with `scan_for()`, so they may span several reads from
+    /// the underlying stream, and they are not included in the records. The stream is split
+    /// in the same way as `slice::split()` splits a slice: the data after the last separator
+    /// is yielded as the final record, even if it is empty, so an empty stream yields a single
+    /// empty record. If reading fails, the error is yielded and the iteration can be resumed
+    /// from the same point.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `sep` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = b"one--two----three";
+    /// let mut ar = AccReader::with_increment(4, input);
+    ///
+    /// let records: Vec<_> = ar.split_on(b"--").map(|r| r.unwrap()).collect();
+    /// assert_eq!(records, [&b"one"[..], b"two", b"", b"three"]);
+    /// ```
+    #[inline]
+    pub fn split_on<'b>(&'b mut self, sep: &'b [u8]) -> SplitOn<'b, R, B> {
+        assert!(!sep.is_empty(), "separator must be non-empty");
+        SplitOn { reader: self, sep, finished: false }
+    }
+
+    /// Returns a reader which reads at most `limit` bytes from the current position of
+    /// this accumulating reader.
+    ///
+    /// Unlike `Read::take()`, the returned reader borrows this accumulating reader, and all
+    /// reads go through it: consumed bytes are accumulated in its buffer and its position
+    /// is advanced accordingly. Therefore, once the returned reader is dropped, this reader
+    /// can seek back over the bytes read through it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Seek, SeekFrom};
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let mut record = Vec::new();
+    /// ar.limited(3).read_to_end(&mut record).unwrap();
+    /// assert_eq!(record, [1, 2, 3]);
+    ///
+    /// assert_eq!(ar.seek(SeekFrom::Current(-2)).unwrap(), 1);
+    /// ```
+    #[inline]
+    pub fn limited(&mut self, limit: u64) -> LimitedAccReader<'_, R, B> {
+        LimitedAccReader { reader: self, limit }
+    }
+
+    /// Reads some bytes into the provided buffer, always going through the internal buffer.
+    ///
+    /// This behaves like `Read::read()`, except that it never reads from the underlying stream
+    /// directly into `buf`: when the buffered data is exhausted, at least `buf.len()` bytes are
+    /// requested from the stream into the internal buffer first, and then copied out of it.
+    /// The result therefore does not depend on the mix of `fill_buf()`, `consume()`, reads
+    /// and seeks made before, and the data read by it is buffered in the same way as with
+    /// `fill_buf()`. `Read::read()` returns the same bytes, but may use the direct path
+    /// for large reads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufRead;
+    ///
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::with_increment(2, input);
+    ///
+    /// let mut buf = [0; 3];
+    /// assert_eq!(ar.fill_buf().unwrap(), &[1, 2]);
+    /// ar.consume(1);
+    /// assert_eq!(ar.invariant_safe_read(&mut buf).unwrap(), 1);
+    /// assert_eq!(ar.invariant_safe_read(&mut buf).unwrap(), 3);
+    /// assert_eq!(buf, [3, 4, 5]);
+    /// ```
+    #[must_use = "fewer bytes than the length of the buffer may be read"]
+    #[inline]
+    pub fn invariant_safe_read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_buffered(buf)
+    }
+
+    /// Reads exactly `n` bytes, returning a slice of the internal buffer containing them.
+    ///
+    /// This is a zero-copy alternative to `Read::read_exact()`: as much data as necessary is
+    /// read from the underlying stream into the internal buffer, and then the current position
+    /// is advanced by `n` bytes. If the stream ends before `n` bytes are available, an error
+    /// of kind `UnexpectedEof` is returned and the current position is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3, 4, 5];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// assert_eq!(ar.read_exact_ref(3).unwrap(), &[1, 2, 3]);
+    /// assert!(ar.read_exact_ref(3).is_err());
+    /// assert_eq!(ar.read_exact_ref(2).unwrap(), &[4, 5]);
+    /// ```
+    pub fn read_exact_ref(&mut self, n: usize) -> io::Result<&[u8]> {
+        let available = self.buf.len() - self.pos;
+        if available < n {
+            self.read_up_to((n - available) as u64)?;
+            if self.buf.len() - self.pos < n {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer"));
+            }
+        }
+
+        let start = self.pos;
+        self.pos += n;
+        self.debug_check_invariants();
+        Ok(&self.buf.as_slice()[start..self.pos])
+    }
+
+    /// Fills as much of `dst` as possible with the data after the current position, returning
+    /// the number of bytes read.
+    ///
+    /// This works like `Read::read_exact()`, except that the stream ending before `dst`
+    /// is full is not an error: the buffered data is copied first, and then the underlying
+    /// stream is read until either `dst` is full or the stream ends. This is convenient for
+    /// filling a preallocated buffer with the rest of the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 2, 3];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// let mut frame = [0; 4];
+    /// assert_eq!(ar.read_remaining_into(&mut frame[1..]).unwrap(), 3);
+    /// assert_eq!(frame, [0, 1, 2, 3]);
+    /// assert_eq!(ar.read_remaining_into(&mut frame).unwrap(), 0);
+    /// ```
+    pub fn read_remaining_into(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        while read < dst.len() {
+            match self.read(&mut dst[read..]) {
+                Ok(0) => break,
+                Ok(n) => read += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(read)
+    }
+
+    /// Reads exactly `N` bytes, returning them as an array.
+    ///
+    /// This is `Read::read_exact()` into a stack array returned by value, which is convenient
+    /// for `from_le_bytes()` and similar conversions. If the stream ends before `N` bytes are
+    /// available, an error of kind `UnexpectedEof` is returned and the current position is left
+    /// unchanged, as long as `N` does not exceed the maximum buffer size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[1, 0, 2, 0, 0, 0];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// assert_eq!(u16::from_le_bytes(ar.read_array().unwrap()), 1);
+    /// assert_eq!(u32::from_le_bytes(ar.read_array().unwrap()), 2);
+    /// assert!(ar.read_array::<1>().is_err());
+    /// ```
+    pub fn read_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut array = [0; N];
+        self.read_exact(&mut array)?;
+        Ok(array)
+    }
+
+    /// Reads a packed binary-coded decimal number consisting of the specified number of
+    /// decimal digits.
+    ///
+    /// Each byte holds two digits, the high nibble first, so `nibbles.div_ceil(2)` bytes are
+    /// read. If `nibbles` is odd, the low nibble of the last byte is not a part of the number
+    /// and is ignored regardless of its value (it often holds a sign in packed decimal formats).
+    ///
+    /// Returns an error of kind `InvalidData` if any of the digits is greater than 9, and an
+    /// error of kind `InvalidInput` without reading anything if `nibbles` is greater than 19,
+    /// because such numbers may not fit into `u64`. The stream position is advanced past the
+    /// read bytes even if they contain an invalid digit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use acc_reader::AccReader;
+    ///
+    /// let input: &[u8] = &[0x12, 0x34, 0x5c];
+    /// let mut ar = AccReader::new(input);
+    ///
+    /// assert_eq!(ar.read_packed_bcd(5).unwrap(), 12345);
+    /// ```
+    pub fn read_packed_bcd(&mut self, nibbles: usize) -> io::Result<u64> {
+        if nibbles > 19 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "too many digits for a u64"));
+        }
+
+        let mut bytes = [0; 10];
+        let bytes = &mut bytes[..nibbles.div_ceil(2)];
+        self.read_exact(bytes)?;
+
+        let mut result = 0;
+        for i in 0..nibbles {
+            let byte = bytes[i / 2];
+            let digit = if i % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+            if digit > 9 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid packed BCD digit"));
+            }
+            result = result * 10 + digit as u64;
+        }
+        Ok(result)
+    }
+
+    #[cfg(feature = "byteorder")]
+    read_int_methods! {
+        read_u16_le: u16, from_le_bytes, "little-endian";
+        read_u16_be: u16, from_be_bytes, "big-endian";
+        read_i16_le: i16, from_le_bytes, "little-endian";
+        read_i16_be: i16, from_be_bytes, "big-endian";
+        read_u32_le: u32, from_le_bytes, "little-endian";
+        read_u32_be: u32, from_be_bytes, "big-endian";
+        read_i32_le: i32, from_le_bytes, "little-endian";
+        read_i32_be: i32, from_be_bytes, "big-endian";
+        read_u64_le: u64, from_le_bytes, "little-endian";
+        read_u64_be: u64, from_be_bytes, "big-endian";
+        read_i64_le: i64, from_le_bytes, "little-endian";
+        read_i64_be: i64, from_be_bytes, "big-endian";
+    }
+
+    // Returns the absolute offset of the end of the buffered data.
+    #[inline]
+    fn buffered_end(&self) -> u64 {
+        self.base + self.buf.len() as u64
+    }
+
+    // Returns the absolute position of the cursor in the stream.
+    #[inline]
+    fn absolute_position(&self) -> u64 {
+        self.base + self.pos as u64
+    }
+
+    // Same as seek_to(), but the target and the returned position are relative
+    // to the origin.
+    fn seek_to_relative(&mut self, target: u64) -> io::Result<u64> {
+        match self.origin.checked_add(target) {
+            Some(target) => self.seek_to(target).map(|target| target - self.origin),
+            None => Err(SeekError::BeyondEnd.into()),
+        }
+    }
+
+    // Moves the cursor to the provided absolute position, reading from the stream
+    // if necessary.
+    fn seek_to(&mut self, target: u64) -> io::Result<u64> {
+        if let Some(seek) = self.source_seek {
+            if target < self.base {
+                return self.restart_at(seek, target);
+            }
+        }
+        self.buffer_up_to(target)?;
+        // reading could have discarded some data, but never after the target
+        self.pos = (target - self.base) as usize;
+        self.debug_check_invariants();
+        Ok(target)
+    }
+
+    // Seeks to the provided distance before the end of the seekable source, accounting
+    // for the logical end, and returns the absolute target.
+    fn seek_end_in_source(&mut self, seek: fn(&mut R, SeekFrom) -> io::Result<u64>, d: u64) -> io::Result<u64> {
+        let source_end = seek(&mut self.source, SeekFrom::End(0))?;
+        let mut len = source_end.saturating_sub(self.source_start);
+        if let Some(end) = self.logical_end {
+            len = cmp::min(len, end);
+        }
+
+        match len.checked_sub(d).filter(|&target| target >= self.origin) {
+            Some(target) if target < self.base || target > self.buffered_end() => self.restart_at(seek, target),
+            target => {
+                // the source has to continue where it has stopped
+                let resume = self.source_start + self.buffered_end();
+                seek(&mut self.source, SeekFrom::Start(resume))?;
+                match target {
+                    Some(target) => self.seek_to(target),
+                    None => Err(SeekError::BeforeStart.into()),
+                }
+            }
+        }
+    }
+
+    // Drops the buffered data and restarts accumulating at the provided absolute offset
+    // of the seekable source.
+    fn restart_at(&mut self, seek: fn(&mut R, SeekFrom) -> io::Result<u64>, target: u64) -> io::Result<u64> {
+        seek(&mut self.source, SeekFrom::Start(self.source_start + target))?;
+        self.buf.truncate(0);
+        self.base = target;
+        self.pos = 0;
+        self.eof = false;
+        self.debug_check_invariants();
+        Ok(target)
+    }
+
+    // Makes sure that the stream is buffered up to the provided absolute position,
+    // reading from the stream if necessary.
+    fn buffer_up_to(&mut self, target: u64) -> io::Result<()> {
+        if target < self.base {
+            return Err(SeekError::Evicted.into());
+        }
+        if target > self.buffered_end() {
+            let need_to_read = target - self.buffered_end();
+            self.read_up_to(need_to_read)?;
+            if target > self.buffered_end() {  // still not enough
+                return Err(SeekError::BeyondEnd.into());
+            }
+        }
+        Ok(())
+    }
+
+    // Discards the data which is too far behind the cursor to stay in the rewind window.
+    // To amortize the cost of shifting the buffer contents, this is only done when the
+    // discarded part is not smaller than the retained one, or when some room may be
+    // needed to stay under the buffer size limit.
+    fn evict(&mut self) {
+        if let Some(window) = self.rewind_window {
+            let evictable = self.pos.saturating_sub(window);
+            if evictable > 0 && (evictable * 2 >= self.buf.len() || self.max_buffer.is_some()) {
+                self.buf.drain_front(evictable);
+                self.base += evictable as u64;
+                self.pos -= evictable;
+            }
+        }
+    }
+
+    // Discards the consumed data, except for the rewind window, if the buffer
+    // is larger than the soft cap.
+    fn trim_to_soft_cap(&mut self) {
+        if let Some(cap) = self.soft_cap {
+            if self.buf.len() > cap {
+                let evictable = self.pos.saturating_sub(self.rewind_window.unwrap_or(0));
+                if evictable > 0 {
+                    self.buf.drain_front(evictable);
+                    self.base += evictable as u64;
+                    self.pos -= evictable;
+                }
+            }
+        }
+    }
+
+    // Verifies the invariants of the reader in debug builds. This is called at the end
+    // of the methods which change the position or the buffered data.
+    #[inline]
+    fn debug_check_invariants(&self) {
+        debug_assert!(self.pos <= self.buf.len(), "position {} is beyond the buffered {} bytes",
+                      self.pos, self.buf.len());
+        // the buffer size limit can only be exceeded because of push_front()
+        if let Some(end) = self.logical_end {
+            debug_assert!(self.buffered_end() <= end, "buffered data ends at {}, beyond the logical end {}",
+                          self.buffered_end(), end);
+        }
+        if let Some(budget) = self.read_budget {
+            debug_assert!(self.source_read <= budget, "read {} bytes, but the budget is {}",
+                          self.source_read, budget);
+        }
+        if let Some(ref starts) = self.line_starts {
+            debug_assert!(starts.windows(2).all(|w| w[0] < w[1]), "line index is not sorted");
+            debug_assert!(starts.last().is_none_or(|&start| start <= self.buffered_end()),
+                          "line index is beyond the buffered data");
+        }
+    }
+
+    // Checks whether data can be read from the stream without going through fetch().
+    #[inline]
+    fn is_unbounded(&self) -> bool {
+        self.max_buffer.is_none() && self.rewind_window.is_none() && self.logical_end.is_none() &&
+            self.read_budget.is_none() && self.zero_read_retries == 0
+    }
+
+    // Returns the number of bytes to request from the stream when the buffer is exhausted.
+    #[inline]
+    fn fill_size(&self) -> usize {
+        match self.read_size {
+            Some(read_size) => read_size,
+            None if self.doubling => cmp::max(self.inc, cmp::min(self.buf.len(), MAX_GROWTH_CHUNK)),
+            None => self.inc,
+        }
+    }
+
+    #[inline]
+    fn notify_source_read(&mut self, n: usize) {
+        self.source_read += n as u64;
+        if let Some(ref mut f) = self.on_source_read {
+            f(n);
+        }
+    }
+
+    // Reports the number of bytes buffered so far by the current operation.
+    #[inline]
+    fn report_progress(&mut self, total: u64) {
+        if let Some(ref mut f) = self.progress {
+            f(total);
+        }
+    }
+
+    // Counts a reallocation if the buffer capacity has grown since it was old_capacity.
+    #[inline]
+    fn track_realloc(&mut self, old_capacity: usize) {
+        if self.buf.capacity() > old_capacity {
+            self.reallocs += 1;
+        }
+    }
+
+    // Updates the high-water mark of the buffer length.
+    #[inline]
+    fn track_peak(&mut self) {
+        self.peak_len = cmp::max(self.peak_len, self.buf.len());
+    }
+
+    // Appends data which was read from the stream bypassing the internal buffer.
+    #[inline]
+    fn append_to_buffer(&mut self, data: &[u8]) {
+        let old_capacity = self.buf.capacity();
+        let old_len = self.buf.len();
+        self.buf.extend_from_slice(data);
+        self.track_realloc(old_capacity);
+        self.track_peak();
+        self.hash_new_data(old_len);
+        self.index_new_data(old_len);
+    }
+
+    // Feeds the data appended to the buffer after old_len to the hasher, if any.
+    #[inline]
+    fn hash_new_data(&mut self, old_len: usize) {
+        #[cfg(feature = "hashing")]
+        {
+            if let Some(ref mut hasher) = self.hasher {
+                hasher.write(&self.buf.as_slice()[old_len..]);
+            }
+        }
+        #[cfg(not(feature = "hashing"))]
+        let _ = old_len;
+    }
+
+    // Adds the lines starting in the data appended to the buffer after old_len
+    // to the line index, if any.
+    fn index_new_data(&mut self, old_len: usize) {
+        if let Some(ref mut starts) = self.line_starts {
+            let new_data = &self.buf.as_slice()[old_len..];
+            for (i, _) in new_data.iter().enumerate().filter(|&(_, &b)| b == b'\n') {
+                starts.push(self.base + (old_len + i) as u64 + 1);
+            }
+        }
+    }
+
+    // Forgets the lines starting after the end of the buffered data, which
+    // has just been cut off.
+    fn truncate_line_index(&mut self) {
+        let end = self.buffered_end();
+        if let Some(ref mut starts) = self.line_starts {
+            while starts.last().is_some_and(|&start| start > end) {
+                starts.pop();
+            }
+        }
+    }
+
+    // Performs a single read of at most n bytes from the stream into the internal
+    // buffer, respecting the buffer size limit. Returns the number of bytes read.
+    #[allow(clippy::io_other_error)]  // acid_io does not provide io::Error::other()
+    fn fetch(&mut self, n: usize) -> io::Result<usize> {
+        self.evict();
+
+        let n = match self.logical_end {
+            Some(end) => cmp::min(n as u64, end.saturating_sub(self.buffered_end())) as usize,
+            None => n,
+        };
+        if n == 0 {
+            return Ok(0);
+        }
+
+        let n = match self.read_budget {
+            Some(budget) => {
+                let left = budget - self.source_read;
+                if left == 0 {
+                    return Err(io::Error::new(io::ErrorKind::Other, "read budget exceeded"));
+                }
+                cmp::min(n as u64, left) as usize
+            }
+            None => n,
+        };
+
+        let n = match self.max_buffer {
+            Some(max_buffer) => {
+                // the buffer can only be over the limit because of push_front()
+                let room = max_buffer.saturating_sub(self.buf.len());
+                if room == 0 && n > 0 {
+                    return Err(io::Error::new(io::ErrorKind::Other, "buffer size limit exceeded"));
+                }
+                cmp::min(n, room)
+            }
+            None => n,
+        };
+
+        let old_capacity = self.buf.capacity();
+        let old_len = self.buf.len();
+        let mut zero_reads = 0;
+        let result = loop {
+            let result = match self.transform {
+                Some(ref mut transform) => self.buf.read_transformed_from(&mut self.source, n, &mut **transform),
+                None => self.buf.read_from(&mut self.source, n),
+            };
+            match result {
+                Err(ref e) if self.retry_interrupted && e.kind() == io::ErrorKind::Interrupted => {}
+                Ok(0) if n > 0 && zero_reads < self.zero_read_retries => {
+                    zero_reads += 1;
+                    self.notify_source_read(0);
+                }
+                result => break result,
+            }
+        };
+        self.track_realloc(old_capacity);
+        self.track_peak();
+        self.hash_new_data(old_len);
+        self.index_new_data(old_len);
+
+        let read = result?;
+        self.notify_source_read(read);
+        if read == 0 && n > 0 {
+            self.eof = true;
+        }
+        self.debug_check_invariants();
+        Ok(read)
+    }
+
+    // Keeps an error from reading ahead while some data is available, so that the data is
+    // returned first and the error by the next fill_buf(). WouldBlock is not kept, because
+    // it only means that no more data is available yet.
+    fn defer_fill_error(&mut self, e: io::Error) {
+        #[cfg(feature = "std")]
+        {
+            if e.kind() == io::ErrorKind::WouldBlock {
+                return;
+            }
+        }
+        self.fill_error = Some(e);
+    }
+
+    // Reads until at least eager_fill bytes are available after the cursor. Errors are only
+    // returned if nothing is available, and deferred otherwise.
+    fn fill_eagerly(&mut self) -> io::Result<()> {
+        let target = match self.max_lookahead {
+            Some(max_lookahead) => cmp::min(self.eager_fill, max_lookahead),
+            None => self.eager_fill,
+        };
+        while self.buf.len() - self.pos < target {
+            let need = target - (self.buf.len() - self.pos);
+            let n = match self.max_lookahead {
+                Some(_) => need,
+                None => cmp::max(need, self.fill_size()),
+            };
+            match self.fetch(n) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    if self.buf.len() == self.pos {
+                        return Err(e);
+                    }
+                    self.defer_fill_error(e);
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Reads everything left in the stream into the internal buffer.
+    fn fetch_to_end(&mut self) -> io::Result<()> {
+        if self.eof {  // everything is already buffered
+            return Ok(());
+        }
+
+        // the stream is read in chunks, so that the buffer limits are checked and the progress
+        // is reported between them; unless the read size is set, the chunks grow with
+        // the buffer to keep the number of reads as low as with Read::read_to_end()
+        let mut total = 0;
+        loop {
+            let n = match self.read_size {
+                Some(read_size) => read_size,
+                None => cmp::max(self.fill_size(), cmp::min(self.buf.len(), MAX_GROWTH_CHUNK)),
+            };
+            match self.fetch(n) {
+                Ok(0) => return Ok(()),
+                Ok(n) => {
+                    total += n as u64;
+                    self.report_progress(total);
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    // Copies the buffered data after the cursor into buf, requesting at least buf.len()
+    // bytes from the stream into the internal buffer first if there is none. This is
+    // the path of all reads which don't go directly to the stream.
+    fn read_buffered(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.buf.len() && !buf.is_empty() {
+            let n = cmp::max(buf.len(), self.read_size.unwrap_or(0));
+            self.fetch(n)?;
+        }
+
+        let n = cmp::min(self.buf.len() - self.pos, buf.len());
+        buf[..n].copy_from_slice(&self.buf.as_slice()[self.pos..self.pos + n]);
+        self.pos += n;
+        self.trim_to_soft_cap();
+        self.debug_check_invariants();
+        Ok(n)
+    }
+
+    // Read from the stream into the internal buffer as much as possible,
+    // but no more than the provided number of bytes.
+    // Returns the number of bytes read; everything read is kept in the buffer
+    // even in case of errors.
+    fn read_up_to(&mut self, n: u64) -> io::Result<usize> {
+        let (read, result) = self.read_up_to_partial(n);
+        result.map(|_| read)
+    }
+
+    // Same as read_up_to(), but also reports the number of bytes read
+    // when an error occurs.
+    fn read_up_to_partial(&mut self, n: u64) -> (usize, io::Result<()>) {
+        // n may be far larger than the stream (e.g. when skipping to its end), so neither
+        // the reservation nor a single read may be sized by it alone
+        let chunk = cmp::max(self.fill_size(), MAX_GROWTH_CHUNK) as u64;
+        let old_capacity = self.buf.capacity();
+        self.buf.reserve(cmp::min(n, chunk) as usize);
+        self.track_realloc(old_capacity);
+
+        let mut read = 0;
+        while (read as u64) < n {
+            let remaining = n - read as u64;
+            let request = match self.read_size {
+                Some(read_size) => cmp::min(remaining, read_size as u64) as usize,
+                None => cmp::min(remaining, chunk) as usize,
+            };
+            match self.fetch(request) {
+                Ok(0) => break,
+                Ok(k) => {
+                    read += k;
+                    self.report_progress(read as u64);
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return (read, Err(e)),
+            }
+        }
+        (read, Ok(()))
+    }
+}
+
+impl<R: Read, B: Buffer> Read for AccReader<R, B> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.buf.len() && self.is_unbounded() &&
+           self.read_size.is_none_or(|read_size| buf.len() >= read_size) {
+            let read = loop {
+                match self.source.read(buf) {
+                    Err(ref e) if self.retry_interrupted && e.kind() == io::ErrorKind::Interrupted => {}
+                    result => break result?,
+                }
+            };
+            self.notify_source_read(read);
+            if read == 0 && !buf.is_empty() {
+                self.eof = true;
+            }
+            if let Some(ref mut transform) = self.transform {
+                transform(&mut buf[..read]);
+            }
+            self.append_to_buffer(&buf[..read]);
+            self.pos += read;
+            self.trim_to_soft_cap();
+            self.debug_check_invariants();
+            Ok(read)
+        } else {  // either some data is buffered, or the buffer needs to be kept under control
+            self.read_buffered(buf)
+        }
+    }
+
+    // Buffers all of the requested data before copying it, so that the position is
+    // not changed on failure; requests which can't be buffered at once are streamed instead.
+    fn read_exact(&mut self, mut buf: &mut [u8]) -> io::Result<()> {
+        if self.max_buffer.is_none_or(|max_buffer| buf.len() <= max_buffer) {
+            while self.buf.len() - self.pos < buf.len() {
+                let need = buf.len() - (self.buf.len() - self.pos);
+                match self.fetch(cmp::max(need, self.read_size.unwrap_or(0))) {
+                    Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer")),
+                    Ok(_) => {}
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            buf.copy_from_slice(&self.buf.as_slice()[self.pos..self.pos + buf.len()]);
+            self.pos += buf.len();
+            self.trim_to_soft_cap();
+            self.debug_check_invariants();
+            return Ok(());
+        }
+
+        while !buf.is_empty() {
+            match self.read(buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    let rest = buf;
+                    buf = &mut rest[n..];
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if buf.is_empty() {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer"))
+        }
+    }
+
+    // Buffers the rest of the stream and validates all of the unread data at once.
+    // Neither the string nor the current position are changed in case of errors.
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        self.fetch_to_end()?;
+        let s = str::from_utf8(&self.buf.as_slice()[self.pos..])
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-8"))?;
+        buf.push_str(s);
+        self.pos += s.len();
+        self.debug_check_invariants();
+        Ok(s.len())
+    }
+
+    // Copies as much of the buffered data as possible across all of the slices, so that
+    // the stream is only read from when the buffer is exhausted.
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        if self.pos == self.buf.len() {
+            let total = bufs.iter().fold(0usize, |acc, b| acc.saturating_add(b.len()));
+            if total == 0 {
+                return Ok(0);
+            }
+            let n = cmp::max(total, self.read_size.unwrap_or(0));
+            self.fetch(n)?;
+        }
+
+        // read_buffered() never reads from the stream here, because it is only called
+        // while some data is buffered
+        let mut read = 0;
+        for buf in bufs {
+            if self.pos == self.buf.len() && !buf.is_empty() {
+                break;
+            }
+            read += self.read_buffered(buf)?;
+        }
+        Ok(read)
+    }
+
+    // Same as read_buffered(), but avoids initializing the destination: data is copied from
+    // the internal buffer, reading it from the stream first if necessary. At most the usual fill
+    // size is requested, because the capacity of the cursor may be arbitrarily large
+    // (e.g. the spare capacity of the vector in Read::read_to_end()).
+    #[cfg(feature = "read_buf")]
+    fn read_buf(&mut self, mut cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        if self.pos == self.buf.len() && cursor.capacity() > 0 {
+            let n = match self.read_size {
+                Some(read_size) => read_size,
+                None => cmp::min(cursor.capacity(), self.fill_size()),
+            };
+            self.fetch(n)?;
+        }
+
+        let available = cmp::min(self.buf.len() - self.pos, cursor.capacity());
+        cursor.append(&self.buf.as_slice()[self.pos..self.pos + available]);
+        self.pos += available;
+        self.trim_to_soft_cap();
+        self.debug_check_invariants();
+        Ok(())
+    }
+}
+
+impl<R: Read, B: Buffer> BufRead for AccReader<R, B> {
+    /// Returns the buffered data after the current position, reading from the underlying
+    /// stream if there is none.
+    ///
+    /// More data may be read even if some is available, see `with_eager_fill()` and
+    /// `with_fill_top_up()`. The data returned by the previous call always stays at
+    /// the beginning of the returned slice.
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if let Some(e) = self.fill_error.take() {
+            return Err(e);
+        }
+
+        let available = self.buf.len() - self.pos;  // self.buf.len() >= pos
+        let inc = match self.max_lookahead {
+            Some(max_lookahead) => cmp::min(self.fill_size(), max_lookahead),
+            None => self.fill_size(),
+        };
+        if available < self.eager_fill {
+            self.fill_eagerly()?;
+        } else if available == 0 {
+            self.fetch(inc)?;
+        } else if self.fill_top_up && available < inc && !self.eof &&
+                  self.last_fill == self.absolute_position() {
+            // some data is available, so it is returned even if reading fails
+            if let Err(e) = self.fetch(inc - available) {
+                self.defer_fill_error(e);
+            }
+        }
+        self.last_fill = self.absolute_position();
+        Ok(&self.buf.as_slice()[self.pos..])
+    }
+
+    /// Marks `amt` bytes returned by `fill_buf()` as read.
+    ///
+    /// Consuming more bytes than `fill_buf()` has returned is a logic error. In debug builds
+    /// this causes a panic; in release builds the position is moved to the end of the buffered
+    /// data instead.
+    fn consume(&mut self, amt: usize) {
+        debug_assert!(amt <= self.buf.len() - self.pos,
+                      "consumed {} bytes, but only {} are available", amt, self.buf.len() - self.pos);
+        self.pos = cmp::min(self.pos.saturating_add(amt), self.buf.len());
+        self.trim_to_soft_cap();
+        self.debug_check_invariants();
+    }
+
+    // Scans the already buffered data first and only reads from the source
+    // when the delimiter is not found there, so everything read stays retained.
+    fn read_until(&mut self, byte: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
+        // offsets are relative to the cursor, because reading may discard
+        // data at the front of the buffer
+        let mut scanned = 0;
+        let mut error = None;
+        let len = loop {
+            let start = self.pos + scanned;
+            if let Some(i) = self.buf.as_slice()[start..].iter().position(|&b| b == byte) {
+                break scanned + i + 1;
+            }
+            scanned = self.buf.len() - self.pos;
+
+            let inc = self.fill_size();
+            match self.fetch(inc) {
+                Ok(0) => break scanned,
+                Ok(_) => {}
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => { error = Some(e); break scanned; }
+            }
+        };
+
+        // as with the default implementation, everything read so far is consumed
+        // even in case of errors
+        buf.extend_from_slice(&self.buf.as_slice()[self.pos..self.pos + len]);
+        self.pos += len;
+        self.trim_to_soft_cap();
+
+        if let Some(e) = error {
+            Err(e)
+        } else {
+            self.debug_check_invariants();
+            Ok(len)
+        }
+    }
+}
+
+impl<R: Read, B: Buffer> Seek for AccReader<R, B> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match pos {
+            SeekFrom::End(n) => {
+                if n > 0 {
+                    Err(SeekError::BeyondEnd.into())
+                } else {
+                    let d = n.unsigned_abs();
+                    if let (Some(seek), false) = (self.source_seek, self.eof) {
+                        return self.seek_end_in_source(seek, d).map(|target| target - self.origin);
+                    }
+                    match self.known_len {
+                        // seek forward from the start, unless the real end is already known
+                        Some(len) if !self.eof => {
+                            let end = len.saturating_sub(self.origin);
+                            if d > end {
+                                return Err(SeekError::BeforeStart.into());
+                            }
+                            self.seek_to_relative(end - d).map_err(|e| {
+                                match e.get_ref().and_then(|e| e.downcast_ref::<SeekError>()) {
+                                    Some(&SeekError::BeyondEnd) => io::Error::new(
+                                        io::ErrorKind::UnexpectedEof, "stream is shorter than its declared length"
+                                    ),
+                                    _ => e,
+                                }
+                            })
+                        }
+                        _ => {
+                            // just read everything that's left and seek from that
+                            self.fetch_to_end()?;
+
+                            let end = self.buffered_end().saturating_sub(self.origin);
+                            if d > end {
+                                Err(SeekError::BeforeStart.into())
+                            } else {
+                                self.seek_to_relative(end - d)
+                            }
+                        }
+                    }
+                }
+            }
+            SeekFrom::Start(n) => self.seek_to_relative(n),
+            SeekFrom::Current(0) => { Ok(self.position()) }
+            SeekFrom::Current(n) => {
+                // the current position may be before the origin, so the target is computed
+                // from the absolute one
+                let current = self.absolute_position();
+                let target = if n < 0 {
+                    current.checked_sub(n.unsigned_abs())
+                } else {
+                    current.checked_add(n as u64)
+                };
+                match target {
+                    Some(target) if target >= self.origin => self.seek_to(target).map(|target| target - self.origin),
+                    Some(_) => Err(SeekError::BeforeStart.into()),
+                    None if n < 0 => Err(SeekError::BeforeStart.into()),
+                    None => Err(SeekError::BeyondEnd.into()),
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position())
+    }
+}
+
+/// Provides the whole data accumulated so far, including the bytes which were already read.
+///
+/// This is the full history of the stream, as opposed to `AccReader::buffer()`, which
+/// returns only the unread part after the current position. If the reader was configured
+/// with a rewind window, only the retained part of the history is available.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Read;
+///
+/// use acc_reader::AccReader;
+///
+/// let input: &[u8] = &[1, 2, 3];
+/// let mut ar = AccReader::new(input);
+///
+/// let mut buf = [0; 2];
+/// ar.read_exact(&mut buf).unwrap();
+/// assert_eq!(ar.as_ref(), &[1, 2]);
+/// ```
+impl<R: Read, B: Buffer> AsRef<[u8]> for AccReader<R, B> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.buf.as_slice()
+    }
+}
+
+/// Compares accumulating readers by their underlying streams, accumulated data, current
+/// positions and increments.
+///
+/// This is mostly useful in tests, for example to check that different sequences of
+/// operations leave readers in the same state.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{Read, Seek, SeekFrom};
+///
+/// use acc_reader::AccReader;
+///
+/// let input: &[u8] = &[1, 2, 3, 4];
+/// let mut ar1 = AccReader::new(input);
+/// let mut ar2 = AccReader::new(input);
+///
+/// ar1.seek(SeekFrom::Start(2)).unwrap();
+/// let mut buf = [0; 2];
+/// ar2.read_exact(&mut buf).unwrap();
+/// assert!(ar1 == ar2);
+/// ```
+impl<R: Read + PartialEq, B: Buffer> PartialEq for AccReader<R, B> {
+    fn eq(&self, other: &AccReader<R, B>) -> bool {
+        self.source == other.source &&
+            self.buf.as_slice() == other.buf.as_slice() &&
+            self.base == other.base &&
+            self.pos == other.pos &&
+            self.inc == other.inc
+    }
+}
+
+/// Provides random access to the accumulated data by ranges.
+///
+/// Indices are the same as in the slice returned by the `AsRef<[u8]>` implementation, that is,
+/// they cover only the bytes which are currently buffered. Indexing never reads from
+/// the underlying stream and panics if the range is out of bounds, just like slice indexing.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Read;
+///
+/// use acc_reader::AccReader;
+///
+/// let input: &[u8] = &[1, 2, 3, 4];
+/// let mut ar = AccReader::new(input);
+///
+/// let mut buf = [0; 3];
+/// ar.read_exact(&mut buf).unwrap();
+/// assert_eq!(&ar[1..3], &[2, 3]);
+/// assert_eq!(&ar[..2], &[1, 2]);
+/// assert_eq!(&ar[2..], &[3]);
+/// assert_eq!(&ar[..], &[1, 2, 3]);
+/// ```
+impl<R: Read, B: Buffer> Index<Range<usize>> for AccReader<R, B> {
+    type Output = [u8];
+
+    #[inline]
+    fn index(&self, index: Range<usize>) -> &[u8] {
+        &self.buf.as_slice()[index]
+    }
+}
+
+impl<R: Read, B: Buffer> Index<RangeFrom<usize>> for AccReader<R, B> {
+    type Output = [u8];
+
+    #[inline]
+    fn index(&self, index: RangeFrom<usize>) -> &[u8] {
+        &self.buf.as_slice()[index]
+    }
+}
+
+impl<R: Read, B: Buffer> Index<RangeTo<usize>> for AccReader<R, B> {
+    type Output = [u8];
+
+    #[inline]
+    fn index(&self, index: RangeTo<usize>) -> &[u8] {
+        &self.buf.as_slice()[index]
+    }
+}
+
+impl<R: Read, B: Buffer> Index<RangeFull> for AccReader<R, B> {
+    type Output = [u8];
+
+    #[inline]
+    fn index(&self, _: RangeFull) -> &[u8] {
+        self.buf.as_slice()
+    }
+}
+
+/// A builder for accumulating readers which allows configuring all of their options.
+///
+/// The builder starts with the default options, which are the same as those used by
+/// `AccReader::new()`: initial capacity of `DEFAULT_BUF_CAPACITY`, increment of
+/// `DEFAULT_BUF_INCREMENT` and no limits. The `with_*` constructors of `AccReader` are
+/// shortcuts for configuring a single option.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io;
+///
+/// use acc_reader::AccReaderBuilder;
+///
+/// let mut ar = AccReaderBuilder::new()
+///     .capacity(512)
+///     .increment(128)
+///     .max_buffer(Some(1024 * 1024))
+///     .rewind_window(Some(4096))
+///     .build(io::stdin());
+/// ```
+#[derive(Clone)]
+pub struct AccReaderBuilder {
+    capacity: usize,
+    increment: usize,
+    read_size: Option<usize>,
+    max_lookahead: Option<usize>,
+    max_buffer: Option<usize>,
+    rewind_window: Option<usize>,
+    read_budget: Option<u64>,
+    known_length: Option<u64>,
+    line_index: bool,
+    soft_cap: Option<usize>,
+    on_source_read: Option<Factory<SourceReadCallback>>,
+    progress: Option<Factory<ProgressCallback>>,
+    #[cfg(feature = "hashing")]
+    hasher: Option<Factory<BoxedHasher>>,
+}
+
+// The functions are not Debug, so only their presence is shown.
+impl fmt::Debug for AccReaderBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("AccReaderBuilder");
+        d.field("capacity", &self.capacity)
+            .field("increment", &self.increment)
+            .field("read_size", &self.read_size)
+            .field("max_lookahead", &self.max_lookahead)
+            .field("max_buffer", &self.max_buffer)
+            .field("rewind_window", &self.rewind_window)
+            .field("read_budget", &self.read_budget)
+            .field("known_length", &self.known_length)
+            .field("line_index", &self.line_index)
+            .field("soft_cap", &self.soft_cap)
+            .field("on_source_read", &self.on_source_read.is_some())
+            .field("progress", &self.progress.is_some());
+        #[cfg(feature = "hashing")]
+        d.field("hasher", &self.hasher.is_some());
+        d.finish()
+    }
+}
+
+impl Default for AccReaderBuilder {
+    #[inline]
+    fn default() -> AccReaderBuilder {
+        AccReaderBuilder::new()
+    }
+}
+
+impl AccReaderBuilder {
+    /// Creates a new builder with the default options.
+    #[inline]
+    pub fn new() -> AccReaderBuilder {
+        AccReaderBuilder {
+            capacity: DEFAULT_BUF_CAPACITY,
+            increment: DEFAULT_BUF_INCREMENT,
+            read_size: None,
+            max_lookahead: None,
+            max_buffer: None,
+            rewind_window: None,
+            read_budget: None,
+            known_length: None,
+            line_index: false,
+            soft_cap: None,
+            on_source_read: None,
+            progress: None,
+            #[cfg(feature = "hashing")]
+            hasher: None,
+        }
+    }
+
+    /// Sets the initial capacity of the internal buffer.
+    ///
+    /// This option is ignored by `build_with_custom_buffer()`.
+    #[inline]
+    pub fn capacity(mut self, capacity: usize) -> AccReaderBuilder {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets the increment of the internal buffer, that is, the number of bytes requested
+    /// from the underlying stream when `fill_buf()` finds no more data in the buffer.
+    #[inline]
+    pub fn increment(mut self, increment: usize) -> AccReaderBuilder {
+        self.increment = increment;
+        self
+    }
+
+    /// Sets the number of bytes requested from the underlying stream by a single read call.
+    ///
+    /// See `AccReader::with_read_size()` for more information.
+    #[inline]
+    pub fn read_size(mut self, read_size: Option<usize>) -> AccReaderBuilder {
+        self.read_size = read_size;
+        self
+    }
+
+    /// Sets the maximum number of bytes which non-consuming operations like `fill_buf()`
+    /// buffer ahead of the current position.
+    ///
+    /// See `AccReader::with_max_lookahead()` for more information.
+    #[inline]
+    pub fn max_lookahead(mut self, max_lookahead: Option<usize>) -> AccReaderBuilder {
+        self.max_lookahead = max_lookahead;
+        self
+    }
+
+    /// Sets the maximum size of the internal buffer.
+    ///
+    /// When set, the internal buffer never grows beyond `max_buffer` bytes. Operations which
+    /// need to read more data from the underlying stream while the buffer is full fail with
+    /// an error of kind `Other`; this includes seeking with `SeekFrom::End` on a stream which
+    /// does not fit in the buffer. Combined with `rewind_window()`, this bounds the memory
+    /// used by the reader while processing arbitrarily large streams.
+    #[inline]
+    pub fn max_buffer(mut self, max_buffer: Option<usize>) -> AccReaderBuilder {
+        self.max_buffer = max_buffer;
+        self
+    }
+
+    /// Sets the number of bytes before the current position which are guaranteed to be kept
+    /// in the internal buffer.
+    ///
+    /// By default everything ever read is retained. When a rewind window is set, data which
+    /// is more than `rewind_window` bytes behind the current position may be discarded when
+    /// the buffer grows, and seeking to it afterwards fails with `SeekError::Evicted`, which
+    /// has the kind `InvalidInput`. Positions reported by the reader stay absolute, i.e. they
+    /// are offsets from the beginning of the underlying stream regardless of how much was
+    /// discarded.
+    #[inline]
+    pub fn rewind_window(mut self, rewind_window: Option<usize>) -> AccReaderBuilder {
+        self.rewind_window = rewind_window;
+        self
+    }
+
+    /// Sets the size of the internal buffer above which the data before the current position
+    /// is discarded as it is consumed.
+    ///
+    /// See `AccReader::with_soft_cap()` for more information.
+    #[inline]
+    pub fn soft_cap(mut self, soft_cap: Option<usize>) -> AccReaderBuilder {
+        self.soft_cap = soft_cap;
+        self
+    }
+
+    /// Sets the maximum total number of bytes which may be read from the underlying stream.
+    ///
+    /// See `AccReader::with_read_budget()` for more information.
+    #[inline]
+    pub fn read_budget(mut self, read_budget: Option<u64>) -> AccReaderBuilder {
+        self.read_budget = read_budget;
+        self
+    }
+
+    /// Sets the total length of the underlying stream, if it is known in advance.
+    ///
+    /// See `AccReader::with_known_length()` for more information.
+    #[inline]
+    pub fn known_length(mut self, known_length: Option<u64>) -> AccReaderBuilder {
+        self.known_length = known_length;
+        self
+    }
+
+    /// Sets whether the offsets at which lines start are indexed.
+    ///
+    /// See `AccReader::with_line_index()` for more information.
+    #[inline]
+    pub fn line_index(mut self, line_index: bool) -> AccReaderBuilder {
+        self.line_index = line_index;
+        self
+    }
+
+    /// Sets a function which is called with the number of bytes returned by each read from
+    /// the underlying stream.
+    ///
+    /// See `AccReader::on_source_read()` for more information. Each reader built by this
+    /// builder gets its own clone of `f`.
+    pub fn on_source_read<F>(mut self, f: F) -> AccReaderBuilder
+        where F: FnMut(usize) + Clone + Send + Sync + 'static
+    {
+        self.on_source_read = Some(Arc::new(move || Box::new(f.clone()) as SourceReadCallback));
+        self
+    }
+
+    /// Sets a function which reports the progress of operations which may read a lot of data
+    /// from the underlying stream.
+    ///
+    /// See `AccReader::with_progress()` for more information. Each reader built by this
+    /// builder gets its own clone of `f`.
+    pub fn progress<F>(mut self, f: F) -> AccReaderBuilder
+        where F: FnMut(u64) + Clone + Send + Sync + 'static
+    {
+        self.progress = Some(Arc::new(move || Box::new(f.clone()) as ProgressCallback));
+        self
+    }
+
+    /// Sets a hasher which is fed with all of the data read from the underlying stream.
+    ///
+    /// See `AccReader::with_hasher()` for more information. Each reader built by this
+    /// builder gets its own clone of `hasher`, in the state it has when this method is called.
+    #[cfg(feature = "hashing")]
+    pub fn hasher<H>(mut self, hasher: H) -> AccReaderBuilder
+        where H: Hasher + Clone + Send + Sync + 'static
+    {
+        self.hasher = Some(Arc::new(move || Box::new(hasher.clone()) as BoxedHasher));
+        self
+    }
+
+    /// Creates a new accumulating reader from the provided `Read` instance with the options
+    /// of this builder.
+    #[inline]
+    pub fn build<R: Read>(self, source: R) -> AccReader<R> {
+        let buf = Vec::with_capacity(self.capacity);
+        self.build_with_custom_buffer(buf, source)
+    }
+
+    /// Creates a new accumulating reader from the provided `Read` instance which stores
+    /// the accumulated data in the provided buffer, with the options of this builder.
+    ///
+    /// Any data already present in the buffer is discarded.
+    pub fn build_with_custom_buffer<R: Read, B: Buffer>(self, mut buf: B, source: R) -> AccReader<R, B> {
+        buf.truncate(0);
+        AccReader {
+            source,
+            buf,
+            base: 0,
+            pos: 0,
+            inc: self.increment,
+            read_size: self.read_size,
+            max_lookahead: self.max_lookahead,
+            max_buffer: self.max_buffer,
+            rewind_window: self.rewind_window,
+            logical_end: None,
+            read_budget: self.read_budget,
+            source_read: 0,
+            known_len: self.known_length,
+            soft_cap: self.soft_cap,
+            line_starts: if self.line_index { Some(vec![0]) } else { None },
+            origin: 0,
+            doubling: false,
+            retry_interrupted: true,
+            zero_read_retries: 0,
+            eager_fill: 0,
+            fill_top_up: false,
+            fill_error: None,
+            mark: None,
+            last_fill: 0,
+            eof: false,
+            reallocs: 0,
+            peak_len: 0,
+            source_seek: None,
+            source_start: 0,
+            on_source_read: self.on_source_read.map(|f| f()),
+            progress: self.progress.map(|f| f()),
+            transform: None,
+            #[cfg(feature = "hashing")]
+            hasher: self.hasher.map(|f| f()),
+        }
+    }
+}
+
+/// An iterator over fixed-size chunks of an accumulating reader.
+///
+/// This struct is created by the `chunks()` method on `AccReader`.
+pub struct Chunks<'a, R: Read + 'a, B: Buffer + 'a = Vec<u8>> {
+    reader: &'a mut AccReader<R, B>,
+    size: usize,
+    // error which happened after a part of the previous chunk was read
+    error: Option<io::Error>,
+}
+
+impl<'a, R: Read, B: Buffer> Iterator for Chunks<'a, R, B> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<io::Result<Vec<u8>>> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+
+        let mut chunk = vec![0; self.size];
+        let mut read = 0;
+        while read < chunk.len() {
+            match self.reader.read(&mut chunk[read..]) {
+                Ok(0) => break,
+                Ok(n) => read += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) if read == 0 => return Some(Err(e)),
+                Err(e) => {
+                    // the data read so far has already been consumed, so it is not dropped
+                    self.error = Some(e);
+                    break;
+                }
+            }
+        }
+
+        if read == 0 {
+            None
+        } else {
+            chunk.truncate(read);
+            Some(Ok(chunk))
+        }
+    }
+}
+
+/// A read-only cursor over the data accumulated by an accumulating reader.
+///
+/// This struct is created by the `cursor_at()` method on `AccReader`. Its positions are
+/// absolute offsets in the stream, like those of `AccReader`.
+#[derive(Clone, Debug)]
+pub struct BufferCursor<'a> {
+    data: &'a [u8],
+    // absolute offset of data[0] in the stream
+    base: u64,
+    // invariant: pos <= data.len()
+    pos: usize,
+}
+
+impl<'a> Read for BufferCursor<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = cmp::min(self.data.len() - self.pos, buf.len());
+        buf[..n].copy_from_slice(&self.data[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl<'a> BufRead for BufferCursor<'a> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(&self.data[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = cmp::min(self.pos.saturating_add(amt), self.data.len());
+    }
+}
+
+impl<'a> Seek for BufferCursor<'a> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = self.base + self.pos as u64;
+        let end = self.base + self.data.len() as u64;
+        let target = resolve_buffered(pos, self.base, position, end)?;
+        self.pos = (target - self.base) as usize;
+        Ok(target)
+    }
+}
+
+// Computes the absolute target of a seek within the buffered data, which spans from base
+// to end, with SeekFrom::End meaning the end of the buffered data.
+fn resolve_buffered(pos: SeekFrom, base: u64, position: u64, end: u64) -> io::Result<u64> {
+    let target = match pos {
+        SeekFrom::Start(n) => Some(n),
+        SeekFrom::Current(n) if n < 0 => position.checked_sub(n.unsigned_abs()),
+        SeekFrom::Current(n) => Some(position + n as u64),
+        SeekFrom::End(n) if n < 0 => end.checked_sub(n.unsigned_abs()),
+        SeekFrom::End(n) => Some(end + n as u64),
+    };
+    match target {
+        None => Err(SeekError::BeforeStart.into()),
+        Some(target) if target > end => Err(SeekError::BeyondEnd.into()),
+        Some(target) if target < base => Err(SeekError::Evicted.into()),
+        Some(target) => Ok(target),
+    }
+}
+
+/// An iterator over the lines of an accumulating reader together with their offsets.
+///
+/// This struct is created by the `lines_seekable()` method on `AccReader`.
+pub struct SeekableLines<'a, R: Read + 'a, B: Buffer + 'a = Vec<u8>> {
+    reader: &'a mut AccReader<R, B>,
+}
+
+impl<'a, R: Read, B: Buffer> Iterator for SeekableLines<'a, R, B> {
+    type Item = io::Result<(u64, String)>;
+
+    fn next(&mut self) -> Option<io::Result<(u64, String)>> {
+        let offset = self.reader.position();
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => None,
+            Ok(_) => {
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+                Some(Ok((offset, line)))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// An iterator over the records of an accumulating reader separated by a byte sequence.
+///
+/// This struct is created by the `split_on()` method on `AccReader`.
+pub struct SplitOn<'a, R: Read + 'a, B: Buffer + 'a = Vec<u8>> {
+    reader: &'a mut AccReader<R, B>,
+    sep: &'a [u8],
+    finished: bool,
+}
+
+impl<'a, R: Read, B: Buffer> Iterator for SplitOn<'a, R, B> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<io::Result<Vec<u8>>> {
+        if self.finished {
+            return None;
+        }
+
+        match self.reader.scan_for(self.sep) {
+            Ok(Some(offset)) => {
+                let len = (offset - self.reader.absolute_position()) as usize;
+                Some(self.reader.read_exact_ref(len + self.sep.len()).map(|r| r[..len].to_vec()))
+            }
+            Ok(None) => {
+                let mut record = Vec::new();
+                let result = self.reader.read_to_end(&mut record);
+                if result.is_ok() {
+                    self.finished = true;
+                }
+                Some(result.map(|_| record))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// A reader which limits the number of bytes read from an accumulating reader.
+///
+/// This struct is created by the `limited()` method on `AccReader`.
+pub struct LimitedAccReader<'a, R: Read + 'a, B: Buffer + 'a = Vec<u8>> {
+    reader: &'a mut AccReader<R, B>,
+    limit: u64,
+}
+
+impl<'a, R: Read, B: Buffer> LimitedAccReader<'a, R, B> {
+    /// Returns the number of bytes that can still be read before this reader
+    /// reports the end of stream.
+    #[inline]
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+}
+
+impl<'a, R: Read, B: Buffer> Read for LimitedAccReader<'a, R, B> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let max = cmp::min(buf.len() as u64, self.limit) as usize;
+        let read = self.reader.read(&mut buf[..max])?;
+        self.limit -= read as u64;
+        Ok(read)
+    }
+}
+
+impl<'a, R: Read, B: Buffer> BufRead for LimitedAccReader<'a, R, B> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.limit == 0 {
+            return Ok(&[]);
+        }
+        let buf = self.reader.fill_buf()?;
+        let available = cmp::min(buf.len() as u64, self.limit) as usize;
+        Ok(&buf[..available])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = cmp::min(amt as u64, self.limit) as usize;
+        self.reader.consume(amt);
+        self.limit -= amt as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
+    use super::*;
+
+    // A reader which returns the scripted results in order, and then reports EOF.
+    struct ScriptedReader(VecDeque<io::Result<Vec<u8>>>);
+
+    impl ScriptedReader {
+        fn new(script: Vec<io::Result<Vec<u8>>>) -> ScriptedReader {
+            ScriptedReader(script.into_iter().collect())
+        }
+    }
+
+    impl Read for ScriptedReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.pop_front() {
+                None => Ok(0),
+                Some(Err(e)) => Err(e),
+                Some(Ok(data)) => {
+                    let n = cmp::min(buf.len(), data.len());
+                    buf[..n].copy_from_slice(&data[..n]);
+                    if n < data.len() {
+                        self.0.push_front(Ok(data[n..].to_vec()));
+                    }
+                    Ok(n)
+                }
+            }
+        }
+    }
+
+    fn would_block() -> io::Result<Vec<u8>> {
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "would block"))
+    }
+
+    #[test]
+    fn test_acc_reader_read() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3];
+        let mut reader = AccReader::new(inner);
+
+        let mut buf = [0, 0];
+        assert_eq!(reader.read(&mut buf).unwrap(), 2);
+        assert_eq!(buf, [5, 6]);
+        assert_eq!(reader.read(&mut buf).unwrap(), 2);
+        assert_eq!(buf, [7, 0]);
+        assert_eq!(reader.read(&mut buf).unwrap(), 2);
+        assert_eq!(buf, [1, 2]);
+        assert_eq!(reader.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], 3);
+    }
+
+    #[test]
+    fn test_acc_reader_read_exact_atomic() {
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6]), Ok(vec![7]), Ok(vec![])]);
+        let mut reader = AccReader::new(inner);
+
+        let mut buf = [0; 1];
+        reader.read_exact(&mut buf).unwrap();
+        let mut buf = [0; 4];
+        assert_eq!(reader.read_exact(&mut buf).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.stream_position().unwrap(), 1);
+        assert_eq!(reader.buffer(), &[6, 7]);
+        reader.read_exact(&mut buf[..2]).unwrap();
+        assert_eq!(buf[..2], [6, 7]);
+
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6]), would_block(), Ok(vec![7, 0])]);
+        let mut reader = AccReader::new(inner);
+        let mut buf = [0; 3];
+        assert_eq!(reader.read_exact(&mut buf).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+        assert_eq!(reader.stream_position().unwrap(), 0);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [5, 6, 7]);
+
+        // too large to be buffered at once
+        let inner: Vec<u8> = (0..20).collect();
+        let mut reader = AccReaderBuilder::new().increment(4).max_buffer(Some(8)).rewind_window(Some(0))
+            .build(&inner[..]);
+        let mut buf = [0; 12];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..], inner[..12]);
+    }
+
+    #[test]
     fn test_acc_reader_buf_read() {
         let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
         let mut reader = AccReader::with_initial_capacity_and_increment(3, 3, inner);
 
-        assert_eq!(reader.fill_buf().ok(), Some(&[5, 6, 7][..]));
-        reader.consume(3);
-        assert_eq!(reader.fill_buf().ok(), Some(&[0, 1, 2][..]));
+        assert_eq!(reader.fill_buf().ok(), Some(&[5, 6, 7][..]));
+        reader.consume(3);
+        assert_eq!(reader.fill_buf().ok(), Some(&[0, 1, 2][..]));
+        reader.consume(3);
+        assert_eq!(reader.fill_buf().ok(), Some(&[3, 4][..]));
+        reader.consume(2);
+        assert_eq!(reader.fill_buf().ok(), Some(&[][..]));
+    }
+
+    #[test]
+    fn test_acc_reader_interleaved_operations() {
+        fn configs(data: &[u8]) -> Vec<AccReader<ScriptedReader>> {
+            // the source returns the data in uneven chunks
+            let source = || {
+                let mut chunks = Vec::new();
+                let mut rest = data;
+                let mut size = 1;
+                while !rest.is_empty() {
+                    let n = cmp::min(size, rest.len());
+                    chunks.push(Ok(rest[..n].to_vec()));
+                    rest = &rest[n..];
+                    size = size % 5 + 1;
+                }
+                ScriptedReader::new(chunks)
+            };
+            vec![
+                AccReader::new(source()),
+                AccReader::with_increment(1, source()),
+                AccReader::with_initial_capacity_and_increment(2, 3, source()),
+                AccReader::with_read_size(4, source()),
+                AccReader::with_max_lookahead(2, source()),
+                AccReader::with_known_length(data.len() as u64, source()),
+                AccReader::with_increment(2, source()).with_growth_strategy(Growth::Doubling),
+                AccReaderBuilder::new().increment(3).max_buffer(Some(1000)).build(source()),
+            ]
+        }
+
+        let data: Vec<u8> = (0..200u32).map(|i| (i * 7 % 251) as u8).collect();
+        for (config, mut reader) in configs(&data).into_iter().enumerate() {
+            let mut seed = 12345u32 + config as u32;
+            let mut next = move |bound: usize| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (seed >> 16) as usize % bound
+            };
+
+            let mut pos = 0;
+            let mut buf = [0; 8];
+            for step in 0..1000 {
+                match next(7) {
+                    0 => {
+                        let len = next(buf.len() + 1);
+                        let n = reader.read(&mut buf[..len]).unwrap();
+                        assert_eq!(&buf[..n], &data[pos..pos + n], "config {}, step {}", config, step);
+                        assert!(n > 0 || len == 0 || pos == data.len(), "config {}, step {}", config, step);
+                        pos += n;
+                    }
+                    1 => {
+                        let len = next(buf.len() + 1);
+                        let n = reader.invariant_safe_read(&mut buf[..len]).unwrap();
+                        assert_eq!(&buf[..n], &data[pos..pos + n], "config {}, step {}", config, step);
+                        assert!(n > 0 || len == 0 || pos == data.len(), "config {}, step {}", config, step);
+                        pos += n;
+                    }
+                    2 => {
+                        let available = reader.fill_buf().unwrap();
+                        assert_eq!(available, &data[pos..pos + available.len()], "config {}, step {}", config, step);
+                        let amt = next(available.len() + 1);
+                        reader.consume(amt);
+                        pos += amt;
+                    }
+                    3 => {
+                        let target = next(data.len() + 1);
+                        assert_eq!(reader.seek(SeekFrom::Start(target as u64)).unwrap(), target as u64);
+                        pos = target;
+                    }
+                    4 => {
+                        let offset = next(9) as i64 - 4;
+                        let target = pos as i64 + offset;
+                        if target >= 0 && target <= data.len() as i64 {
+                            reader.seek(SeekFrom::Current(offset)).unwrap();
+                            pos = target as usize;
+                        }
+                    }
+                    5 if next(20) == 0 => {
+                        let offset = next(10);
+                        reader.seek(SeekFrom::End(-(offset as i64))).unwrap();
+                        pos = data.len() - offset;
+                    }
+                    _ => {
+                        let len = next(buf.len() + 1);
+                        if pos + len <= data.len() {
+                            reader.read_exact(&mut buf[..len]).unwrap();
+                            assert_eq!(&buf[..len], &data[pos..pos + len], "config {}, step {}", config, step);
+                            pos += len;
+                        }
+                    }
+                }
+                assert_eq!(reader.stream_position().unwrap(), pos as u64, "config {}, step {}", config, step);
+            }
+
+            reader.seek(SeekFrom::Start(0)).unwrap();
+            let mut all = Vec::new();
+            reader.read_to_end(&mut all).unwrap();
+            assert_eq!(all, data, "config {}", config);
+        }
+    }
+
+    // A buffer relying on the default implementations of the reading methods.
+    struct SimpleBuffer(Vec<u8>);
+
+    impl Buffer for SimpleBuffer {
+        fn len(&self) -> usize { self.0.len() }
+        fn as_slice(&self) -> &[u8] { &self.0 }
+        fn extend_from_slice(&mut self, data: &[u8]) { self.0.extend_from_slice(data) }
+        fn truncate(&mut self, len: usize) { self.0.truncate(len) }
+        fn drain_front(&mut self, n: usize) { self.0.drain(..n); }
+    }
+
+    #[test]
+    fn test_acc_reader_custom_buffer() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+        let mut reader = AccReader::with_custom_buffer(SimpleBuffer(vec![9, 9]), inner);
+
+        let mut buf = [0, 0];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [5, 6]);
+        assert_eq!(reader.fill_buf().ok(), Some(&[7, 0, 1, 2, 3, 4][..]));
+
+        assert_eq!(reader.seek(SeekFrom::End(-3)).unwrap(), 5);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [2, 3]);
+
+        assert_eq!(reader.seek(SeekFrom::Start(1)).unwrap(), 1);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [6, 7]);
+    }
+
+    #[test]
+    fn test_acc_reader_max_lookahead() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+        let mut reader = AccReader::with_max_lookahead(3, inner);
+
+        assert_eq!(reader.fill_buf().ok(), Some(&[5, 6, 7][..]));
+        assert_eq!(reader.fill_buf().ok(), Some(&[5, 6, 7][..]));
+        reader.consume(2);
+        assert_eq!(reader.fill_buf().ok(), Some(&[7][..]));
+        reader.consume(1);
+        assert_eq!(reader.fill_buf().ok(), Some(&[0, 1, 2][..]));
+
+        // seeking is not limited
+        assert_eq!(reader.seek(SeekFrom::Start(7)).unwrap(), 7);
+        assert_eq!(reader.fill_buf().ok(), Some(&[4][..]));
+
+        let mut reader = AccReader::with_max_lookahead(0, inner);
+        assert_eq!(reader.fill_buf().ok(), Some(&[][..]));
+    }
+
+    #[test]
+    fn test_acc_reader_at_offset() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+        let mut reader = AccReader::new(inner);
+
+        let mut buf = [0, 0];
+        reader.read_exact(&mut buf).unwrap();
+
+        let result = reader.at_offset(5, |r| {
+            let mut buf = [0, 0];
+            r.read_exact(&mut buf).map(|_| buf)
+        });
+        assert_eq!(result.unwrap(), [2, 3]);
+        assert_eq!(reader.stream_position().unwrap(), 2);
+
+        let result: io::Result<()> = reader.at_offset(6, |r| {
+            let mut buf = [0, 0, 0];
+            r.read_exact(&mut buf)
+        });
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.stream_position().unwrap(), 2);
+
+        let result = reader.at_offset(128, |_| Ok(()));
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.stream_position().unwrap(), 2);
+
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [7, 0]);
+    }
+
+    #[test]
+    fn test_acc_reader_chunks() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+        let mut reader = AccReader::with_initial_capacity_and_increment(3, 3, inner);
+
+        {
+            let mut chunks = reader.chunks(3);
+            assert_eq!(chunks.next().unwrap().unwrap(), [5, 6, 7]);
+            assert_eq!(chunks.next().unwrap().unwrap(), [0, 1, 2]);
+        }
+
+        reader.seek(SeekFrom::Start(1)).unwrap();
+        let chunks: Vec<_> = reader.chunks(3).map(|c| c.unwrap()).collect();
+        assert_eq!(chunks, [vec![6, 7, 0], vec![1, 2, 3], vec![4]]);
+        assert!(reader.chunks(3).next().is_none());
+
+        let error = Err(io::Error::new(io::ErrorKind::InvalidData, "broken"));
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6, 7, 0]), error, Ok(vec![1, 2])]);
+        let mut reader = AccReader::with_increment(4, inner);
+        let mut chunks = reader.chunks(3);
+        assert_eq!(chunks.next().unwrap().unwrap(), [5, 6, 7]);
+        assert_eq!(chunks.next().unwrap().unwrap(), [0]);
+        assert_eq!(chunks.next().unwrap().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(chunks.next().unwrap().unwrap(), [1, 2]);
+        assert!(chunks.next().is_none());
+    }
+
+    #[test]
+    fn test_acc_reader_limited() {
+        let inner: &[u8] = &[3, 5, 6, 7, 2, 0, 1, 4];
+        let mut reader = AccReader::new(inner);
+
+        // length-prefixed records
+        let mut len = [0];
+        reader.read_exact(&mut len).unwrap();
+        let mut record = Vec::new();
+        {
+            let mut limited = reader.limited(len[0] as u64);
+            limited.read_to_end(&mut record).unwrap();
+            assert_eq!(limited.limit(), 0);
+        }
+        assert_eq!(record, [5, 6, 7]);
+        assert_eq!(reader.stream_position().unwrap(), 4);
+
+        reader.read_exact(&mut len).unwrap();
+        {
+            let mut limited = reader.limited(len[0] as u64);
+            assert_eq!(limited.fill_buf().ok(), Some(&[0, 1][..]));
+            limited.consume(1);
+            assert_eq!(limited.fill_buf().ok(), Some(&[1][..]));
+            limited.consume(1);
+            assert_eq!(limited.fill_buf().ok(), Some(&[][..]));
+        }
+        assert_eq!(reader.stream_position().unwrap(), 7);
+
+        // the bytes read through the limited reader are retained
+        reader.seek(SeekFrom::Start(1)).unwrap();
+        let mut buf = [0, 0, 0];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [5, 6, 7]);
+    }
+
+    #[test]
+    fn test_acc_reader_read_exact_ref() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+        let mut reader = AccReader::with_initial_capacity_and_increment(2, 2, inner);
+
+        assert_eq!(reader.read_exact_ref(3).unwrap(), &[5, 6, 7]);
+        assert_eq!(reader.read_exact_ref(0).unwrap(), &[]);
+        assert_eq!(reader.read_exact_ref(4).unwrap(), &[0, 1, 2, 3]);
+        assert_eq!(reader.read_exact_ref(2).err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.stream_position().unwrap(), 7);
+        assert_eq!(reader.read_exact_ref(1).unwrap(), &[4]);
+
+        reader.seek(SeekFrom::Start(2)).unwrap();
+        assert_eq!(reader.read_exact_ref(2).unwrap(), &[7, 0]);
+    }
+
+    #[test]
+    fn test_acc_reader_read_remaining_into() {
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6, 7]), Ok(vec![0, 1]), Ok(vec![2])]);
+        let mut reader = AccReader::new(inner);
+
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6, 7]);
+        reader.consume(1);
+        let mut buf = [0; 3];
+        assert_eq!(reader.read_remaining_into(&mut buf).unwrap(), 3);
+        assert_eq!(buf, [6, 7, 0]);
+
+        let mut buf = [0xff; 4];
+        assert_eq!(reader.read_remaining_into(&mut buf).unwrap(), 2);
+        assert_eq!(buf, [1, 2, 0xff, 0xff]);
+        assert_eq!(reader.read_remaining_into(&mut buf).unwrap(), 0);
+        assert_eq!(reader.read_remaining_into(&mut []).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_acc_reader_reserve() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+        let mut reader = AccReader::with_initial_capacity(0, inner);
+        assert_eq!(reader.capacity(), 0);
+
+        reader.reserve(100);
+        assert!(reader.capacity() >= 100);
+
+        let mut buf = [0; 3];
+        reader.read_exact(&mut buf).unwrap();
+        reader.reserve(200);
+        assert!(reader.capacity() >= 203);
+        assert_eq!(reader.stream_position().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_acc_reader_read_packed_bcd() {
+        let inner: &[u8] = &[0x12, 0x34, 0x98, 0x70, 0x5f, 0x1a];
+        let mut reader = AccReader::new(inner);
+
+        assert_eq!(reader.read_packed_bcd(4).unwrap(), 1234);
+        assert_eq!(reader.read_packed_bcd(3).unwrap(), 987);  // trailing 0 nibble ignored
+        assert_eq!(reader.read_packed_bcd(1).unwrap(), 5);  // trailing f nibble ignored
+        assert_eq!(reader.read_packed_bcd(0).unwrap(), 0);
+        assert_eq!(reader.read_packed_bcd(2).err().unwrap().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(reader.read_packed_bcd(2).err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.read_packed_bcd(20).err().unwrap().kind(), io::ErrorKind::InvalidInput);
+
+        let inner: &[u8] = &[0x99; 10];
+        let mut reader = AccReader::new(inner);
+        assert_eq!(reader.read_packed_bcd(19).unwrap(), 9_999_999_999_999_999_999);
+    }
+
+    #[test]
+    fn test_acc_reader_read_until() {
+        let inner: &[u8] = b"first\nsecond record\n\nlast";
+        let mut reader = AccReader::with_initial_capacity_and_increment(4, 4, inner);
+
+        let mut line = Vec::new();
+        assert_eq!(reader.read_until(b'\n', &mut line).unwrap(), 6);
+        assert_eq!(line, b"first\n");
+
+        line.clear();
+        assert_eq!(reader.read_until(b'\n', &mut line).unwrap(), 14);
+        assert_eq!(line, b"second record\n");
+
+        // rewind and re-read the retained records
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        let lines: Vec<_> = reader.by_ref().lines().map(|l| l.unwrap()).collect();
+        assert_eq!(lines, ["first", "second record", "", "last"]);
+
+        reader.seek(SeekFrom::Start(6)).unwrap();
+        line.clear();
+        assert_eq!(reader.read_until(b'\n', &mut line).unwrap(), 14);
+        assert_eq!(line, b"second record\n");
+
+        reader.seek(SeekFrom::End(-4)).unwrap();
+        line.clear();
+        assert_eq!(reader.read_until(b'\n', &mut line).unwrap(), 4);
+        assert_eq!(line, b"last");
+        assert_eq!(reader.read_until(b'\n', &mut line).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_acc_reader_builder() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+        let mut reader = AccReaderBuilder::new().capacity(2).increment(3).build(inner);
+        assert!(reader.capacity() >= 2);
+        assert_eq!(reader.fill_buf().ok(), Some(&[5, 6, 7][..]));
+
+        let mut reader = AccReaderBuilder::new().increment(3).max_lookahead(Some(2)).build(inner);
+        assert_eq!(reader.fill_buf().ok(), Some(&[5, 6][..]));
+    }
+
+    #[test]
+    fn test_acc_reader_max_buffer() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+        let mut reader = AccReaderBuilder::new().max_buffer(Some(5)).build(inner);
+
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [5, 6, 7, 0]);
+        assert_eq!(reader.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], 1);
+        assert_eq!(reader.read(&mut buf).err().unwrap().kind(), io::ErrorKind::Other);
+        assert_eq!(reader.seek(SeekFrom::End(0)).err().unwrap().kind(), io::ErrorKind::Other);
+        assert_eq!(reader.seek(SeekFrom::Start(6)).err().unwrap().kind(), io::ErrorKind::Other);
+
+        // everything buffered so far is still available
+        assert_eq!(reader.seek(SeekFrom::Start(1)).unwrap(), 1);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [6, 7, 0, 1]);
+    }
+
+    #[test]
+    fn test_acc_reader_rewind_window() {
+        let inner: Vec<u8> = (0..100).collect();
+        let mut reader = AccReaderBuilder::new()
+            .increment(4)
+            .max_buffer(Some(8))
+            .rewind_window(Some(2))
+            .build(&inner[..]);
+
+        // streaming through the whole input never exceeds the limit
+        let mut all = Vec::new();
+        reader.read_to_end(&mut all).unwrap();
+        assert_eq!(all, inner);
+        assert_eq!(reader.stream_position().unwrap(), 100);
+
+        // the rewind window is retained
+        assert_eq!(reader.seek(SeekFrom::Current(-2)).unwrap(), 98);
+        let mut buf = [0; 2];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [98, 99]);
+
+        // just the window is guaranteed to stay
+        let mut reader = AccReaderBuilder::new().increment(4).rewind_window(Some(2)).build(&inner[..]);
+        reader.seek(SeekFrom::Start(50)).unwrap();
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [50, 51, 52, 53]);
+        reader.seek(SeekFrom::Start(90)).unwrap();
+        assert_eq!(reader.seek(SeekFrom::Start(88)).unwrap(), 88);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [88, 89, 90, 91]);
+        assert_eq!(reader.seek(SeekFrom::Start(10)).err().unwrap().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.seek(SeekFrom::Current(-80)).err().unwrap().kind(), io::ErrorKind::InvalidInput);
+
+        // positions stay absolute
+        assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 99);
+        assert_eq!(reader.stream_position().unwrap(), 99);
+        reader.read_exact(&mut buf[..1]).unwrap();
+        assert_eq!(buf[0], 99);
+    }
+
+    #[test]
+    fn test_acc_reader_soft_cap() {
+        let inner: Vec<u8> = (0..100_000).map(|i| i as u8).collect();
+        let mut reader = AccReaderBuilder::new()
+            .capacity(16)
+            .increment(16)
+            .soft_cap(Some(64))
+            .rewind_window(Some(4))
+            .build(&inner[..]);
+
+        let mut total = 0;
+        loop {
+            let n = reader.fill_buf().unwrap().len();
+            if n == 0 {
+                break;
+            }
+            reader.consume(cmp::min(n, 5));
+            total += cmp::min(n, 5);
+            assert!(reader.as_ref().len() <= 80);
+        }
+        assert_eq!(total, inner.len());
+        assert!(reader.capacity() <= 256);
+
+        // the rewind window is retained
+        assert_eq!(reader.seek(SeekFrom::Current(-4)).unwrap(), 99_996);
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, inner[99_996..]);
+
+        let mut reader = AccReader::with_soft_cap(8, &inner[..]);
+        let mut buf = [0; 10];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(reader.seek(SeekFrom::Start(0)).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+        // vectored reads go through the same path
+        let mut reader = AccReaderBuilder::new().increment(16).soft_cap(Some(64)).build(&inner[..]);
+        let (mut a, mut b) = ([0; 3], [0; 7]);
+        for _ in 0..100 {
+            let n = reader.read_vectored(&mut [io::IoSliceMut::new(&mut a), io::IoSliceMut::new(&mut b)]).unwrap();
+            assert!(n > 0);
+            assert!(reader.as_ref().len() <= 80);
+        }
+
+        let mut reader = AccReaderBuilder::new().increment(16).soft_cap(Some(64)).build(&inner[..]);
+        let mut line = Vec::new();
+        for _ in 0..100 {
+            line.clear();
+            assert_eq!(reader.read_until(255, &mut line).unwrap(), 256);
+            assert!(reader.as_ref().len() <= 16);
+        }
+    }
+
+    #[test]
+    fn test_acc_reader_is_fully_buffered() {
+        let inner: &[u8] = &[5, 6, 7, 0];
+
+        let mut reader = AccReader::new(inner);
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf).unwrap();
+        assert!(!reader.is_fully_buffered());
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+        assert!(reader.is_fully_buffered());
+
+        let mut reader = AccReader::new(inner);
+        let mut all = Vec::new();
+        reader.read_to_end(&mut all).unwrap();
+        assert!(reader.is_fully_buffered());
+
+        let mut reader = AccReader::new(inner);
+        reader.seek(SeekFrom::End(-2)).unwrap();
+        assert!(reader.is_fully_buffered());
+
+        let mut reader = AccReader::with_increment(2, inner);
+        assert_eq!(reader.fill_buf().ok(), Some(&[5, 6][..]));
+        reader.consume(2);
+        assert_eq!(reader.fill_buf().ok(), Some(&[7, 0][..]));
+        reader.consume(2);
+        assert!(!reader.is_fully_buffered());
+        assert_eq!(reader.fill_buf().ok(), Some(&[][..]));
+        assert!(reader.is_fully_buffered());
+    }
+
+    #[test]
+    fn test_acc_reader_as_ref() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::with_increment(4, inner);
+        assert_eq!(reader.as_ref(), &[]);
+
+        let mut buf = [0; 2];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(reader.as_ref(), &[5, 6]);
+        assert_eq!(reader.buffer(), &[]);
+
+        reader.fill_buf().unwrap();
+        reader.consume(1);
+        assert_eq!(reader.as_ref(), &[5, 6, 7, 0, 1, 2]);
+        assert_eq!(reader.buffer(), &[0, 1, 2]);
+
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        assert_eq!(reader.as_ref(), &[5, 6, 7, 0, 1, 2]);
+        assert_eq!(reader.buffer(), &[5, 6, 7, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_acc_reader_copy_to() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+        let mut reader = AccReader::new(inner);
+
+        let mut buf = [0; 2];
+        reader.read_exact(&mut buf).unwrap();
+
+        let mut copy = Vec::new();
+        assert_eq!(reader.copy_to(&mut copy).unwrap(), 6);
+        assert_eq!(copy, &inner[2..]);
+        assert!(reader.is_fully_buffered());
+        assert_eq!(reader.copy_to(&mut copy).unwrap(), 0);
+
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        let mut all = Vec::new();
+        reader.read_to_end(&mut all).unwrap();
+        assert_eq!(all, inner);
+    }
+
+    #[test]
+    fn test_acc_reader_seek() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+        let mut reader = AccReader::new(inner);
+
+        let mut buf = [0, 0];
+
+        assert_eq!(reader.seek(SeekFrom::Start(2)).unwrap(), 2);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [7, 0]);
+
+        assert_eq!(reader.seek(SeekFrom::Current(-1)).unwrap(), 3);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0, 1]);
+
+        assert_eq!(reader.seek(SeekFrom::End(-3)).unwrap(), 5);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [2, 3]);
+
+        // seek to beyond the end and to before the start
+        assert_eq!(reader.seek(SeekFrom::End(3)).err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.seek(SeekFrom::Current(-128)).err().unwrap().kind(), io::ErrorKind::InvalidInput);
+
+        // seek as far back as possible
+        assert_eq!(reader.seek(SeekFrom::End(i64::MIN)).err().unwrap().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.seek(SeekFrom::Current(i64::MIN)).err().unwrap().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.stream_position().unwrap(), 7);
+
+        // seek to the end from the start
+        let mut reader = AccReader::new(inner);
+        assert_eq!(reader.seek(SeekFrom::Start(inner.len() as u64)).unwrap(), inner.len() as u64);
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+
+        // seek to beyond the end from the start
+        let mut reader = AccReader::new(inner);
+        assert_eq!(reader.seek(SeekFrom::Start(128)).err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_acc_reader_seek_would_block() {
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6, 7]), would_block(), Ok(vec![0, 1, 2])]);
+        let mut reader = AccReader::new(inner);
+
+        assert_eq!(reader.seek(SeekFrom::Start(5)).err().unwrap().kind(), io::ErrorKind::WouldBlock);
+        assert_eq!(reader.stream_position().unwrap(), 0);
+
+        // the retried seek resumes from the buffered data
+        assert_eq!(reader.seek(SeekFrom::Start(5)).unwrap(), 5);
+        let mut buf = [0];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [2]);
+
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        let mut all = Vec::new();
+        reader.read_to_end(&mut all).unwrap();
+        assert_eq!(all, [5, 6, 7, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_acc_reader_try_fill_buf() {
+        let inner = ScriptedReader::new(vec![would_block(), Ok(vec![5, 6]), would_block(), Ok(vec![])]);
+        let mut reader = AccReader::new(inner);
+
+        assert_eq!(reader.try_fill_buf().unwrap(), None);
+        assert_eq!(reader.try_fill_buf().unwrap(), Some(&[5, 6][..]));
+        reader.consume(1);
+        assert_eq!(reader.try_fill_buf().unwrap(), Some(&[6][..]));
+        reader.consume(1);
+        assert_eq!(reader.try_fill_buf().unwrap(), None);
+        assert_eq!(reader.try_fill_buf().unwrap(), Some(&[][..]));
+
+        let inner = ScriptedReader::new(vec![Err(io::Error::new(io::ErrorKind::InvalidData, "failure"))]);
+        let mut reader = AccReader::new(inner);
+        assert_eq!(reader.try_fill_buf().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_acc_reader_fill_more() {
+        let inner = ScriptedReader::new(vec![
+            Ok(vec![5, 6]), would_block(), would_block(), Ok(vec![7, 0, 1]),
+        ]);
+        let mut reader = AccReader::new(inner);
+
+        assert_eq!(reader.fill_more(4).unwrap(), 2);
+        assert_eq!(reader.fill_more(4).err().unwrap().kind(), io::ErrorKind::WouldBlock);
+        assert_eq!(reader.fill_more(4).unwrap(), 3);
+        assert_eq!(reader.fill_more(4).unwrap(), 0);
+        assert_eq!(reader.stream_position().unwrap(), 0);
+        assert_eq!(reader.buffer(), &[5, 6, 7, 0, 1]);
+    }
+
+    #[test]
+    fn test_acc_reader_index() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::new(inner);
+
+        reader.seek(SeekFrom::Start(4)).unwrap();
+        assert_eq!(&reader[1..3], &[6, 7]);
+        assert_eq!(&reader[2..], &[7, 0]);
+        assert_eq!(&reader[..1], &[5]);
+        assert_eq!(&reader[..], &[5, 6, 7, 0]);
+        assert_eq!(reader.stream_position().unwrap(), 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_acc_reader_index_out_of_range() {
+        let inner: &[u8] = &[5, 6, 7];
+        let reader = AccReader::new(inner);
+        let _ = &reader[0..1];
+    }
+
+    #[test]
+    fn test_acc_reader_seek_relative() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::new(inner);
+
+        reader.seek_relative(4).unwrap();
+        assert_eq!(reader.stream_position().unwrap(), 4);
+        reader.seek_relative(-3).unwrap();
+        assert_eq!(reader.stream_position().unwrap(), 1);
+        reader.seek_relative(0).unwrap();
+        assert_eq!(reader.stream_position().unwrap(), 1);
+
+        assert_eq!(reader.seek_relative(-2).err().unwrap().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.seek_relative(6).err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.stream_position().unwrap(), 1);
+        assert_eq!(reader.fill_buf().unwrap(), &[6, 7, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_acc_reader_realloc_count() {
+        let inner: &[u8] = &[0; 64];
+        let mut reader = AccReader::with_initial_capacity_and_increment(16, 16, inner);
+
+        assert_eq!(reader.fill_buf().unwrap().len(), 16);
+        assert_eq!(reader.realloc_count(), 0);
+
+        reader.seek(SeekFrom::Start(40)).unwrap();
+        assert_eq!(reader.realloc_count(), 1);
+
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        reader.reserve(0);
+        assert_eq!(reader.realloc_count(), 1);
+
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf.len(), 64);
+        assert!(reader.realloc_count() >= 2);
+    }
+
+    #[test]
+    fn test_acc_reader_growth_strategy() {
+        fn stream(growth: Growth) -> (u64, u64) {
+            let inner: Vec<u8> = vec![0; 1 << 16];
+            let mut reader = AccReader::with_initial_capacity_and_increment(16, 16, &inner[..])
+                .with_growth_strategy(growth);
+            let mut reads = 0;
+            loop {
+                let n = reader.fill_buf().unwrap().len();
+                if n == 0 {
+                    break;
+                }
+                reader.consume(n);
+                reads += 1;
+            }
+            (reads, reader.realloc_count())
+        }
+
+        let (fixed_reads, fixed_reallocs) = stream(Growth::Fixed(16));
+        let (doubling_reads, doubling_reallocs) = stream(Growth::Doubling);
+        assert_eq!(fixed_reads, 4096);
+        assert_eq!(doubling_reads, 13);
+        assert!(doubling_reallocs <= fixed_reallocs);
+        assert!(doubling_reallocs <= 13);
+    }
+
+    #[test]
+    fn test_acc_reader_take_buffered() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::with_increment(4, inner);
+
+        assert_eq!(reader.take_buffered(), Vec::<u8>::new());
+
+        reader.fill_buf().unwrap();
+        reader.consume(2);
+        assert_eq!(reader.take_buffered(), [7, 0]);
+        assert_eq!(reader.buffer(), &[]);
+        assert_eq!(reader.stream_position().unwrap(), 2);
+
+        assert_eq!(reader.fill_buf().unwrap(), &[1, 2]);
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, [5, 6, 1, 2]);
+
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::with_increment(4, inner);
+        reader.truncate_at(5);
+        reader.fill_buf().unwrap();
+        reader.consume(1);
+        assert_eq!(reader.take_buffered(), [6, 7, 0]);
+        assert_eq!(reader.fill_buf().unwrap(), &[1]);
+        assert_eq!(reader.seek(SeekFrom::End(0)).unwrap(), 2);
+        assert_eq!(reader.seek(SeekFrom::Start(3)).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+        let mut reader = AccReaderBuilder::new().increment(4).known_length(Some(6)).build(inner);
+        reader.fill_buf().unwrap();
+        assert_eq!(reader.take_buffered(), [5, 6, 7, 0]);
+        assert_eq!(reader.seek(SeekFrom::End(0)).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_acc_reader_push_front() {
+        let inner = ScriptedReader::new(vec![Ok(b"ab\ncd".to_vec()), Ok(b"ef".to_vec())]);
+        let mut reader = AccReaderBuilder::new().line_index(true).build(inner);
+
+        assert_eq!(reader.fill_buf().unwrap(), b"ab\ncd");
+        reader.consume(1);
+        reader.push_front(b"x\n");
+        assert_eq!(reader.as_ref(), b"ax\nb\ncd");
+        assert_eq!(reader.line_offset(1), Some(3));
+        assert_eq!(reader.line_offset(2), Some(5));
+
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).unwrap();
+        assert_eq!(data, b"x\nb\ncdef");
+        assert_eq!(reader.seek_to_line(2).unwrap(), 5);
+
+        let inner: &[u8] = &[5, 6, 7];
+        let mut reader = AccReader::new(inner);
+        reader.truncate_at(2);
+        reader.push_front(&[1]);
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).unwrap();
+        assert_eq!(data, [1, 5, 6]);
+    }
+
+    #[test]
+    fn test_acc_reader_has_data_left() {
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6]), would_block(), Ok(vec![7])]);
+        let mut reader = AccReader::new(inner);
+
+        assert!(reader.has_data_left().unwrap());
+        reader.consume(2);
+        assert_eq!(reader.has_data_left().err().unwrap().kind(), io::ErrorKind::WouldBlock);
+        assert!(reader.has_data_left().unwrap());
+        assert_eq!(reader.stream_position().unwrap(), 2);
+        reader.consume(1);
+        assert!(!reader.has_data_left().unwrap());
+    }
+
+    #[test]
+    fn test_acc_reader_borrowed_source() {
+        let mut inner = io::Cursor::new(vec![5, 6, 7, 0, 1, 2]);
+        {
+            let mut reader = AccReader::with_increment(4, &mut inner);
+            let mut buf = [0; 2];
+            reader.read_exact(&mut buf).unwrap();
+            assert_eq!(buf, [5, 6]);
+            reader.seek(SeekFrom::Start(0)).unwrap();
+            reader.read_exact(&mut buf).unwrap();
+            assert_eq!(buf, [5, 6]);
+
+            let source = reader.into_inner();
+            assert_eq!(source.position(), 2);
+        }
+
+        let mut rest = Vec::new();
+        inner.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [7, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_acc_reader_read_size() {
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6, 7, 0]), Ok(vec![1, 2])]);
+        let mut reader = AccReaderBuilder::new().increment(1).read_size(Some(4)).build(inner);
+
+        let mut buf = [0; 1];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [5]);
+        assert_eq!(reader.buffer(), &[6, 7, 0]);
+
+        assert_eq!(reader.fill_buf().unwrap(), &[6, 7, 0]);
+        reader.consume(3);
+        assert_eq!(reader.fill_buf().unwrap(), &[1, 2]);
+
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::with_read_size(2, inner);
+        reader.seek(SeekFrom::Start(5)).unwrap();
+        assert_eq!(reader.buffer(), &[]);
+        assert_eq!(reader.fill_buf().unwrap(), &[2]);
+    }
+
+    #[test]
+    fn test_acc_reader_partial_eq() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader1 = AccReader::with_increment(4, inner);
+        let mut reader2 = AccReader::with_increment(4, inner);
+        assert!(reader1 == reader2);
+
+        reader1.fill_buf().unwrap();
+        assert!(reader1 != reader2);
+
+        reader2.seek(SeekFrom::Start(4)).unwrap();
+        reader2.seek(SeekFrom::Start(0)).unwrap();
+        assert!(reader1 == reader2);
+
+        reader1.consume(1);
+        let mut buf = [0; 1];
+        reader2.read_exact(&mut buf).unwrap();
+        assert!(reader1 == reader2);
+
+        assert!(reader1 != AccReader::with_increment(1, inner));
+    }
+
+    #[test]
+    fn test_acc_reader_reset() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::new(inner);
+
+        reader.reset();
+        assert_eq!(reader.stream_position().unwrap(), 0);
+
+        reader.seek(SeekFrom::Start(4)).unwrap();
+        reader.reset();
+        assert_eq!(reader.stream_position().unwrap(), 0);
+        assert_eq!(reader.buffer(), &[5, 6, 7, 0]);
+
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, [5, 6, 7, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_acc_reader_clear() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::with_increment(4, inner);
+
+        reader.fill_buf().unwrap();
+        reader.consume(1);
+        reader.clear();
+        assert_eq!(reader.stream_position().unwrap(), 4);
+        assert_eq!(reader.as_ref(), &[]);
+
+        assert_eq!(reader.fill_buf().unwrap(), &[1, 2]);
+        assert_eq!(reader.seek(SeekFrom::Start(3)).err().unwrap().kind(), io::ErrorKind::InvalidInput);
+        reader.seek(SeekFrom::Start(5)).unwrap();
+        reader.reset();
+        assert_eq!(reader.stream_position().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_acc_reader_seek_error() {
+        fn reason(e: io::Error) -> Option<SeekError> {
+            e.get_ref().and_then(|e| e.downcast_ref::<SeekError>()).cloned()
+        }
+
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::new(inner);
+        reader.seek(SeekFrom::Start(2)).unwrap();
+
+        let e = reader.seek(SeekFrom::Current(-3)).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reason(e), Some(SeekError::BeforeStart));
+
+        let e = reader.seek(SeekFrom::End(-7)).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reason(e), Some(SeekError::BeforeStart));
+
+        let e = reader.seek(SeekFrom::Start(7)).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reason(e), Some(SeekError::BeyondEnd));
+
+        let e = reader.seek(SeekFrom::End(1)).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reason(e), Some(SeekError::BeyondEnd));
+
+        assert_eq!(reader.stream_position().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_acc_reader_lines_seekable() {
+        let inner: &[u8] = b"abc\r\nde\n\nf";
+        let mut reader = AccReader::with_increment(2, inner);
+
+        let second = {
+            let mut lines = reader.lines_seekable();
+            assert_eq!(lines.next().unwrap().unwrap(), (0, "abc".to_owned()));
+            let second = lines.next().unwrap().unwrap();
+            assert_eq!(second, (5, "de".to_owned()));
+            second
+        };
+
+        reader.seek(SeekFrom::Start(second.0)).unwrap();
+        let lines: Vec<_> = reader.lines_seekable().map(|l| l.unwrap()).collect();
+        assert_eq!(lines, [second, (8, "".to_owned()), (9, "f".to_owned())]);
+    }
+
+    #[test]
+    fn test_acc_reader_from_chain() {
+        let header: &[u8] = &[5, 6, 7];
+        let body: &[u8] = &[0, 1, 2];
+        let mut reader = AccReader::from_chain(header, body);
+
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [5, 6, 7, 0]);
+        assert_eq!(reader.seek(SeekFrom::Current(-3)).unwrap(), 1);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [6, 7, 0, 1]);
+        assert_eq!(reader.seek(SeekFrom::End(0)).unwrap(), 6);
+
+        let trailer: &[u8] = &[3, 4];
+        let mut reader = reader.append_source(trailer);
+        assert!(!reader.is_fully_buffered());
+        assert_eq!(reader.seek(SeekFrom::Current(-2)).unwrap(), 4);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [1, 2, 3, 4]);
+        assert_eq!(reader.seek(SeekFrom::Start(2)).unwrap(), 2);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [7, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_acc_reader_set_position_unchecked() {
+        let inner: Vec<u8> = (0..20).collect();
+        let mut reader = AccReaderBuilder::new().increment(4).rewind_window(Some(0)).build(&inner[..]);
+        reader.seek(SeekFrom::Start(6)).unwrap();
+        reader.drop_consumed();
+        reader.ensure_buffered(16).unwrap();
+
+        let mut buf = [0; 2];
+        for &offset in &[14, 6, 10] {
+            unsafe { reader.set_position_unchecked(offset); }
+            assert_eq!(reader.stream_position().unwrap(), offset);
+            reader.read_exact(&mut buf).unwrap();
+            assert_eq!(buf, [offset as u8, offset as u8 + 1]);
+        }
+
+        reader.set_origin(4);
+        unsafe { reader.set_position_unchecked(16); }
+        assert_eq!(reader.position(), 12);
+        assert!(reader.buffer().is_empty());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "outside of the buffered data")]
+    fn test_acc_reader_set_position_unchecked_outside() {
+        let inner: &[u8] = &[5, 6, 7];
+        let mut reader = AccReader::new(inner);
+        unsafe { reader.set_position_unchecked(1); }
+    }
+
+    #[test]
+    fn test_acc_reader_ensure_buffered() {
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6]), Ok(vec![7, 0, 1]), Ok(vec![2, 3])]);
+        let mut reader = AccReader::new(inner);
+
+        let mut buf = [0; 1];
+        reader.read_exact(&mut buf).unwrap();
+        reader.ensure_buffered(4).unwrap();
+        assert_eq!(reader.buffer(), &[6, 7, 0]);
+        assert_eq!(reader.stream_position().unwrap(), 1);
+
+        reader.ensure_buffered(3).unwrap();
+        assert_eq!(reader.as_ref().len(), 4);
+        reader.ensure_buffered(100).unwrap();
+        assert_eq!(reader.buffer(), &[6, 7, 0, 1, 2, 3]);
+        assert!(reader.is_fully_buffered());
+        assert_eq!(reader.stream_position().unwrap(), 1);
+
+        let inner: Vec<u8> = (0..200).collect();
+        let mut reader = AccReader::with_max_lookahead(4, &inner[..]);
+        reader.seek(SeekFrom::Start(2)).unwrap();
+        reader.ensure_buffered(100).unwrap();
+        assert_eq!(reader.buffer(), &[2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_acc_reader_slice_range() {
+        let inner: Vec<u8> = (0..20).collect();
+        let mut reader = AccReaderBuilder::new().increment(4).rewind_window(Some(2)).build(&inner[..]);
+
+        let mut buf = [0; 6];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(reader.slice_range(8, 12).unwrap(), [8, 9, 10, 11]);
+        assert_eq!(reader.stream_position().unwrap(), 6);
+        assert_eq!(reader.slice_range(4, 4).unwrap(), []);
+
+        reader.seek(SeekFrom::Start(15)).unwrap();
+        assert_eq!(reader.slice_range(1, 3).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.slice_range(16, 14).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.slice_range(14, 18).unwrap(), [14, 15, 16, 17]);
+        assert_eq!(reader.slice_range(18, 21).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.stream_position().unwrap(), 15);
+    }
+
+    #[test]
+    fn test_acc_reader_split_on() {
+        // separators straddle the read boundaries, and the last record is empty
+        let inner: &[u8] = b"abc|#de|#|#fgh|#";
+        let mut reader = AccReader::with_increment(4, inner);
+
+        let records: Vec<_> = reader.split_on(b"|#").map(|r| r.unwrap()).collect();
+        assert_eq!(records, [&b"abc"[..], b"de", b"", b"fgh", b""]);
+        assert!(reader.split_on(b"|#").next().unwrap().unwrap().is_empty());
+
+        reader.seek(SeekFrom::Start(5)).unwrap();
+        let records: Vec<_> = reader.split_on(b"#|").map(|r| r.unwrap()).collect();
+        assert_eq!(records, [&b"de|"[..], b"#fgh|#"]);
+
+        let inner: &[u8] = b"no separator";
+        let mut reader = AccReader::with_increment(3, inner);
+        let records: Vec<_> = reader.split_on(b"\r\n").map(|r| r.unwrap()).collect();
+        assert_eq!(records, [&b"no separator"[..]]);
+    }
+
+    #[cfg(feature = "read_buf")]
+    #[test]
+    fn test_acc_reader_read_buf() {
+        use std::io::BorrowedBuf;
+        use std::mem::MaybeUninit;
+
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::with_increment(4, inner);
+
+        let mut storage = [MaybeUninit::uninit(); 4];
+        let mut buf = BorrowedBuf::from(&mut storage[..]);
+        reader.read_buf(buf.unfilled()).unwrap();
+        assert_eq!(buf.filled(), &[5, 6, 7, 0]);
+
+        reader.seek(SeekFrom::Start(2)).unwrap();
+        let mut storage = [MaybeUninit::uninit(); 8];
+        let mut buf = BorrowedBuf::from(&mut storage[..]);
+        reader.read_buf(buf.unfilled()).unwrap();
+        assert_eq!(buf.filled(), &[7, 0]);
+        reader.read_buf(buf.unfilled()).unwrap();
+        assert_eq!(buf.filled(), &[7, 0, 1, 2]);
+        reader.read_buf(buf.unfilled()).unwrap();
+        assert_eq!(buf.filled(), &[7, 0, 1, 2]);
+
+        assert_eq!(reader.as_ref(), &[5, 6, 7, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_acc_reader_truncate_at() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::with_increment(2, inner);
+        reader.truncate_at(4);
+
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6]);
+        reader.consume(2);
+        assert!(!reader.is_fully_buffered());
+        assert_eq!(reader.fill_buf().unwrap(), &[7, 0]);
+        reader.consume(2);
+        assert_eq!(reader.fill_buf().unwrap(), &[]);
+        assert!(reader.is_fully_buffered());
+
+        let e = reader.seek(SeekFrom::Start(5)).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 3);
+
+        reader.truncate_at(6);
+        reader.truncate_at(2);
+        assert_eq!(reader.stream_position().unwrap(), 2);
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert!(buf.is_empty());
+        assert_eq!(reader.as_ref(), &[5, 6]);
+    }
+
+    #[test]
+    fn test_acc_reader_truncate_at_current() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::new(inner);
+
+        let mut buf = [0; 3];
+        reader.read_exact(&mut buf).unwrap();
+        reader.truncate_at_current();
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+
+        reader.seek(SeekFrom::Start(1)).unwrap();
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [6, 7]);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "beyond the logical end")]
+    fn test_acc_reader_invariants() {
+        let inner: &[u8] = &[5, 6, 7];
+        let mut reader = AccReader::new(inner);
+        reader.fill_buf().unwrap();
+
+        reader.logical_end = Some(1);
+        reader.reset();
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "consumed 3 bytes, but only 2 are available")]
+    fn test_acc_reader_consume_too_much() {
+        let inner: &[u8] = &[5, 6];
+        let mut reader = AccReader::new(inner);
+        reader.fill_buf().unwrap();
+        reader.consume(3);
+    }
+
+    #[test]
+    fn test_acc_reader_seek_prealloc() {
+        let inner: &[u8] = &[0; 256];
+        let mut reader = AccReader::with_initial_capacity(0, inner);
+
+        reader.seek_prealloc(256);
+        assert!(reader.capacity() >= 256);
+        assert_eq!(reader.realloc_count(), 1);
+
+        reader.seek(SeekFrom::Start(100)).unwrap();
+        reader.seek_prealloc(200);
+        let mut buf = [0; 156];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(reader.realloc_count(), 1);
+
+        // finding the end of the stream is not covered by the preallocation
+        assert_eq!(reader.read(&mut [0; 1]).unwrap(), 0);
+        assert_eq!(reader.as_ref().len(), 256);
+    }
+
+    #[test]
+    fn test_acc_reader_cursor_at() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::new(inner);
+        reader.seek(SeekFrom::Start(1)).unwrap();
+
+        {
+            let mut c1 = reader.cursor_at(4).unwrap();
+            let mut c2 = c1.clone();
+            assert_eq!(c1.fill_buf().unwrap(), &[]);
+            assert_eq!(c1.seek(SeekFrom::Current(-2)).unwrap(), 2);
+            assert_eq!(c2.seek(SeekFrom::End(-4)).unwrap(), 0);
+
+            let mut buf = [0; 2];
+            c1.read_exact(&mut buf).unwrap();
+            assert_eq!(buf, [7, 0]);
+            c2.read_exact(&mut buf).unwrap();
+            assert_eq!(buf, [5, 6]);
+            assert_eq!(c1.read(&mut buf).unwrap(), 0);
+
+            assert_eq!(c2.seek(SeekFrom::Current(-3)).err().unwrap().kind(), io::ErrorKind::InvalidInput);
+            assert_eq!(c2.seek(SeekFrom::Start(5)).err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+        }
+
+        assert_eq!(reader.stream_position().unwrap(), 1);
+        assert_eq!(reader.cursor_at(7).err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.as_ref(), &[5, 6, 7, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_acc_reader_on_source_read() {
+        use std::sync::{Arc, Mutex};
+
+        let reads = Arc::new(Mutex::new(Vec::new()));
+        let log = reads.clone();
+
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::with_increment(4, inner)
+            .on_source_read(move |n| log.lock().unwrap().push(n));
+
+        reader.fill_buf().unwrap();
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        let mut buf = [0; 5];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(*reads.lock().unwrap(), [4, 1]);
+
+        reader.seek(SeekFrom::End(0)).unwrap();
+        assert_eq!(*reads.lock().unwrap(), [4, 1, 1, 0]);
+    }
+
+    #[test]
+    fn test_acc_reader_rewind_available() {
+        let inner: &[u8] = &[0; 64];
+        let mut reader = AccReaderBuilder::new().increment(8).rewind_window(Some(4)).build(inner);
+        assert_eq!(reader.rewind_available(), 0);
+        assert_eq!(reader.forward_buffered(), 0);
+
+        reader.fill_buf().unwrap();
+        reader.consume(6);
+        assert_eq!(reader.rewind_available(), 6);
+        assert_eq!(reader.forward_buffered(), 2);
+
+        reader.seek(SeekFrom::Start(40)).unwrap();
+        reader.fill_buf().unwrap();
+        assert_eq!(reader.rewind_available(), 4);
+        assert_eq!(reader.forward_buffered(), 8);
+        assert!(reader.seek(SeekFrom::Current(-5)).is_err());
+        assert_eq!(reader.seek(SeekFrom::Current(-4)).unwrap(), 36);
+
+        reader.clear();
+        assert_eq!(reader.rewind_available(), 0);
+        assert_eq!(reader.forward_buffered(), 0);
+    }
+
+    #[test]
+    fn test_acc_reader_read_vectored() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::new(inner);
+        reader.seek(SeekFrom::Start(5)).unwrap();
+        reader.seek(SeekFrom::Start(0)).unwrap();
+
+        let (mut a, mut b) = ([0; 2], [0; 4]);
+        {
+            let mut bufs = [io::IoSliceMut::new(&mut a), io::IoSliceMut::new(&mut []), io::IoSliceMut::new(&mut b)];
+            assert_eq!(reader.read_vectored(&mut bufs).unwrap(), 5);
+        }
+        assert_eq!(a, [5, 6]);
+        assert_eq!(b, [7, 0, 1, 0]);
+
+        {
+            let mut bufs = [io::IoSliceMut::new(&mut a), io::IoSliceMut::new(&mut b)];
+            assert_eq!(reader.read_vectored(&mut bufs).unwrap(), 1);
+            assert_eq!(reader.read_vectored(&mut bufs).unwrap(), 0);
+        }
+        assert_eq!(a, [2, 6]);
+    }
+
+    #[test]
+    fn test_acc_reader_rewind_to_last_fill() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::with_increment(3, inner);
+
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6, 7]);
+        reader.consume(3);
+        assert_eq!(reader.fill_buf().unwrap(), &[0, 1, 2]);
+        reader.consume(2);
+        assert_eq!(reader.rewind_to_last_fill().unwrap(), 3);
+        assert_eq!(reader.fill_buf().unwrap(), &[0, 1, 2]);
+
+        reader.consume(1);
+        let mut buf = [0; 2];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(reader.rewind_to_last_fill().unwrap(), 3);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0, 1]);
+    }
+
+    #[test]
+    fn test_acc_reader_mark() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::with_increment(2, inner);
+        assert_eq!(reader.reset_to_mark().unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+        let mut buf = [0; 3];
+        reader.read_exact(&mut buf[..1]).unwrap();
+        reader.mark(3);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [6, 7, 0]);
+        reader.reset_to_mark().unwrap();
+        assert_eq!(reader.stream_position().unwrap(), 1);
+
+        // the mark stays valid after seeking back before it
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        reader.reset_to_mark().unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [6, 7, 0]);
+
+        reader.read_exact(&mut buf[..1]).unwrap();
+        assert_eq!(reader.reset_to_mark().unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        reader.seek(SeekFrom::Start(2)).unwrap();
+        assert_eq!(reader.reset_to_mark().unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_acc_reader_scan_for() {
+        let inner = ScriptedReader::new(vec![
+            Ok(vec![5, 6, 7]), Ok(vec![0xab]), Ok(vec![0xcd, 0xef, 1]), Ok(vec![0xab, 0xcd]), Ok(vec![0xef]),
+        ]);
+        let mut reader = AccReader::with_increment(3, inner);
+
+        assert_eq!(reader.scan_for(&[0xab, 0xcd, 0xef]).unwrap(), Some(3));
+        assert_eq!(reader.stream_position().unwrap(), 0);
+        assert_eq!(reader.as_ref(), &[5, 6, 7, 0xab, 0xcd, 0xef, 1]);
+
+        reader.seek(SeekFrom::Start(4)).unwrap();
+        assert_eq!(reader.scan_for(&[0xab, 0xcd, 0xef]).unwrap(), Some(7));
+        assert_eq!(reader.scan_for(&[]).unwrap(), Some(4));
+        assert_eq!(reader.scan_for(&[0xef, 5]).unwrap(), None);
+
+        reader.seek(SeekFrom::Start(7)).unwrap();
+        let mut buf = [0; 3];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0xab, 0xcd, 0xef]);
+    }
+
+    #[test]
+    fn test_acc_reader_line_index() {
+        let inner = ScriptedReader::new(vec![Ok(b"ab\ncd".to_vec()), Ok(b"\n\nef\ng".to_vec())]);
+        let mut reader = AccReaderBuilder::new().line_index(true).build(inner);
+        let mut line = String::new();
+
+        assert_eq!(reader.line_offset(0), Some(0));
+        assert_eq!(reader.line_offset(1), None);
+        assert_eq!(reader.seek_to_line(1).unwrap(), 3);
+        assert_eq!(reader.line_offset(2), None);
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "cd\n");
+
+        assert_eq!(reader.seek_to_line(4).unwrap(), 10);
+        assert_eq!(reader.line_offset(3), Some(7));
+        assert_eq!(reader.position(), 10);
+        assert_eq!(reader.seek_to_line(5).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.stream_position().unwrap(), 10);
+
+        assert_eq!(reader.seek_to_line(2).unwrap(), 6);
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "\n");
+        assert_eq!(reader.seek_to_line(0).unwrap(), 0);
+
+        // lines after the cut off data are forgotten
+        reader.truncate_at(8);
+        assert_eq!(reader.line_offset(3), Some(7));
+        assert_eq!(reader.line_offset(4), None);
+
+        let inner: &[u8] = b"a\n";
+        let mut reader = AccReader::new(inner);
+        assert_eq!(reader.line_offset(0), None);
+        assert_eq!(reader.seek_to_line(0).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_acc_reader_refresh_end() {
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6]), Ok(vec![]), Ok(vec![7]), Ok(vec![]), Ok(vec![])]);
+        let mut reader = AccReader::new(inner);
+
+        assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 1);
+        assert!(reader.is_fully_buffered());
+        assert_eq!(reader.refresh_end().unwrap(), 3);
+        assert_eq!(reader.stream_position().unwrap(), 1);
+        assert_eq!(reader.seek(SeekFrom::End(0)).unwrap(), 3);
+        assert_eq!(reader.refresh_end().unwrap(), 3);
+
+        let mut rest = Vec::new();
+        reader.seek(SeekFrom::Start(1)).unwrap();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [6, 7]);
+    }
+
+    #[test]
+    fn test_acc_reader_discard_to_end() {
+        let inner: Vec<u8> = (0..100).collect();
+        let mut reader = AccReader::with_initial_capacity_and_increment(4, 4, &inner[..]);
+
+        assert_eq!(reader.fill_buf().unwrap(), &[0, 1, 2, 3]);
+        reader.consume(1);
+        assert_eq!(reader.discard_to_end().unwrap(), 96);
+        assert!(reader.capacity() <= 16);
+        assert_eq!(reader.stream_position().unwrap(), 4);
+        assert!(reader.is_fully_buffered());
+        assert_eq!(reader.discard_to_end().unwrap(), 0);
+
+        assert_eq!(reader.seek(SeekFrom::Start(5)).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.seek(SeekFrom::End(-2)).unwrap(), 2);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [2, 3]);
+
+        let mut reader = AccReader::with_increment(4, &inner[..]);
+        reader.truncate_at(10);
+        assert_eq!(reader.discard_to_end().unwrap(), 10);
+        assert_eq!(reader.seek(SeekFrom::End(0)).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_acc_reader_bytes_until_eof() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::new(inner);
+
+        assert_eq!(reader.bytes_until_eof().unwrap(), 6);
+        assert_eq!(reader.stream_position().unwrap(), 0);
+        assert!(reader.is_fully_buffered());
+
+        reader.seek(SeekFrom::Start(4)).unwrap();
+        assert_eq!(reader.bytes_until_eof().unwrap(), 2);
+        reader.seek(SeekFrom::End(0)).unwrap();
+        assert_eq!(reader.bytes_until_eof().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_acc_reader_boxed() {
+        let inner: &[u8] = &[5, 6, 7];
+        let mut readers: Vec<BoxedAccReader> = vec![
+            AccReader::boxed(inner),
+            AccReader::boxed(io::Cursor::new(vec![0, 1])),
+        ];
+
+        for reader in &mut readers {
+            reader.seek(SeekFrom::End(-1)).unwrap();
+        }
+        let mut buf = [0; 1];
+        readers[0].read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [7]);
+        readers[1].read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [1]);
+
+        let mut reader: BoxedAccReaderSend = AccReader::boxed_send(inner);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [5]);
+    }
+
+    #[test]
+    fn test_acc_reader_seek_end_after_eof() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let reads = Arc::new(AtomicUsize::new(0));
+        let counter = reads.clone();
+
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::new(inner)
+            .on_source_read(move |_| { counter.fetch_add(1, Ordering::SeqCst); });
+
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert!(reader.is_fully_buffered());
+        let reads_after_eof = reads.load(Ordering::SeqCst);
+
+        assert_eq!(reader.seek(SeekFrom::End(-2)).unwrap(), 4);
+        assert_eq!(reader.bytes_until_eof().unwrap(), 2);
+        assert_eq!(reads.load(Ordering::SeqCst), reads_after_eof);
+    }
+
+    #[test]
+    fn test_acc_reader_buffer_fully_zip() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        fn u16_at(data: &[u8]) -> u16 {
+            u16::from(data[0]) | u16::from(data[1]) << 8
+        }
+
+        fn u32_at(data: &[u8]) -> u32 {
+            u32::from(u16_at(data)) | u32::from(u16_at(&data[2..])) << 16
+        }
+
+        // a ZIP archive with two stored (uncompressed) files; CRCs are not checked
+        fn zip_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+            fn push_u16(out: &mut Vec<u8>, n: u16) { out.extend_from_slice(&[n as u8, (n >> 8) as u8]); }
+            fn push_u32(out: &mut Vec<u8>, n: u32) { push_u16(out, n as u16); push_u16(out, (n >> 16) as u16); }
+
+            let mut out = Vec::new();
+            let mut central = Vec::new();
+            for &(name, data) in files {
+                let offset = out.len() as u32;
+                push_u32(&mut out, 0x0403_4b50);
+                for _ in 0..5 { push_u16(&mut out, 0); }
+                for _ in 0..3 { push_u32(&mut out, data.len() as u32); }
+                push_u16(&mut out, name.len() as u16);
+                push_u16(&mut out, 0);
+                out.extend_from_slice(name.as_bytes());
+                out.extend_from_slice(data);
+
+                push_u32(&mut central, 0x0201_4b50);
+                for _ in 0..6 { push_u16(&mut central, 0); }
+                for _ in 0..3 { push_u32(&mut central, data.len() as u32); }
+                push_u16(&mut central, name.len() as u16);
+                for _ in 0..4 { push_u16(&mut central, 0); }
+                push_u32(&mut central, 0);
+                push_u32(&mut central, offset);
+                central.extend_from_slice(name.as_bytes());
+            }
+
+            let central_offset = out.len() as u32;
+            out.extend_from_slice(&central);
+            push_u32(&mut out, 0x0605_4b50);
+            push_u16(&mut out, 0);
+            push_u16(&mut out, 0);
+            push_u16(&mut out, files.len() as u16);
+            push_u16(&mut out, files.len() as u16);
+            push_u32(&mut out, central.len() as u32);
+            push_u32(&mut out, central_offset);
+            push_u16(&mut out, 0);
+            out
+        }
+
+        let archive = zip_archive(&[("a.txt", b"hello"), ("dir/b.bin", &[1, 2, 3, 4, 5, 6, 7])]);
+        let reads = Arc::new(AtomicUsize::new(0));
+        let counter = reads.clone();
+        let mut reader = AccReader::with_increment(16, &archive[..])
+            .on_source_read(move |_| { counter.fetch_add(1, Ordering::SeqCst); });
+
+        assert_eq!(reader.buffer_fully().unwrap(), archive.len() as u64);
+        assert!(reader.is_fully_buffered());
+        assert_eq!(reader.stream_position().unwrap(), 0);
+        let reads_after_buffering = reads.load(Ordering::SeqCst);
+
+        let mut eocd = [0; 22];
+        reader.seek(SeekFrom::End(-22)).unwrap();
+        reader.read_exact(&mut eocd).unwrap();
+        assert_eq!(u32_at(&eocd), 0x0605_4b50);
+        let entries = u16_at(&eocd[10..]);
+        reader.seek(SeekFrom::Start(u64::from(u32_at(&eocd[16..])))).unwrap();
+
+        let mut files = Vec::new();
+        for _ in 0..entries {
+            let mut header = [0; 46];
+            reader.read_exact(&mut header).unwrap();
+            assert_eq!(u32_at(&header), 0x0201_4b50);
+            let mut name = vec![0; usize::from(u16_at(&header[28..]))];
+            reader.read_exact(&mut name).unwrap();
+            let size = u32_at(&header[24..]) as usize;
+            let next_entry = reader.stream_position().unwrap();
+
+            let mut local = [0; 30];
+            reader.seek(SeekFrom::Start(u64::from(u32_at(&header[42..])))).unwrap();
+            reader.read_exact(&mut local).unwrap();
+            assert_eq!(u32_at(&local), 0x0403_4b50);
+            reader.seek(SeekFrom::Current(i64::from(u16_at(&local[26..])))).unwrap();
+            let mut data = vec![0; size];
+            reader.read_exact(&mut data).unwrap();
+            files.push((String::from_utf8(name).unwrap(), data));
+
+            reader.seek(SeekFrom::Start(next_entry)).unwrap();
+        }
+
+        assert_eq!(files, vec![
+            ("a.txt".to_owned(), b"hello".to_vec()),
+            ("dir/b.bin".to_owned(), vec![1, 2, 3, 4, 5, 6, 7]),
+        ]);
+        assert_eq!(reads.load(Ordering::SeqCst), reads_after_buffering);
+        assert_eq!(reader.buffer_fully().unwrap(), archive.len() as u64);
+    }
+
+    #[test]
+    fn test_acc_reader_seek_end_chunked() {
+        let inner: Vec<u8> = (0..100).collect();
+        let mut reader = AccReaderBuilder::new().increment(4).max_buffer(Some(40)).build(&inner[..]);
+        let e = reader.seek(SeekFrom::End(0)).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::Other);
+        assert_eq!(reader.as_ref(), &inner[..40]);
+        assert_eq!(reader.stream_position().unwrap(), 0);
+
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6, 7]), Ok(vec![0, 1]), Ok(vec![2])]);
+        let mut reader = AccReader::with_increment(2, inner);
+        assert_eq!(reader.seek(SeekFrom::End(-4)).unwrap(), 2);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [7, 0, 1, 2]);
+        assert_eq!(reader.seek(SeekFrom::End(-6)).unwrap(), 0);
+        assert_eq!(reader.seek(SeekFrom::End(-7)).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_acc_reader_read_array() {
+        let inner: Vec<u8> = (0..20).collect();
+        let mut reader = AccReader::with_increment(3, &inner[..]);
+
+        reader.seek(SeekFrom::Start(5)).unwrap();
+        let first = reader.read_array::<8>().unwrap();
+        assert_eq!(first, [5, 6, 7, 8, 9, 10, 11, 12]);
+        assert_eq!(reader.position(), 13);
+
+        reader.seek(SeekFrom::Current(-8)).unwrap();
+        assert_eq!(reader.read_array::<8>().unwrap(), first);
+        assert_eq!(reader.read_array::<0>().unwrap(), []);
+
+        let e = reader.read_array::<8>().unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.position(), 13);
+        assert_eq!(reader.read_array::<7>().unwrap(), [13, 14, 15, 16, 17, 18, 19]);
+    }
+
+    #[test]
+    fn test_acc_reader_fill_buf_top_up() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3];
+        let mut reader = AccReader::with_increment(4, inner);
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6, 7, 0]);
+        reader.consume(3);
+        assert_eq!(reader.fill_buf().unwrap(), &[0]);
+        assert_eq!(reader.fill_buf().unwrap(), &[0]);
+
+        let mut reader = AccReader::with_increment(4, inner).with_fill_top_up(true);
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6, 7, 0]);
+        reader.consume(3);
+        assert_eq!(reader.fill_buf().unwrap(), &[0]);
+        assert_eq!(reader.fill_buf().unwrap(), &[0, 1, 2, 3]);
+        assert_eq!(reader.fill_buf().unwrap(), &[0, 1, 2, 3]);
+
+        reader.consume(3);
+        assert_eq!(reader.fill_buf().unwrap(), &[3]);
+        assert_eq!(reader.fill_buf().unwrap(), &[3]);
+        assert_eq!(reader.source_read_offset(), 7);
+
+        let inner = ScriptedReader::new(vec![Ok(vec![5]), would_block(), Ok(vec![6])]);
+        let mut reader = AccReader::with_increment(4, inner).with_fill_top_up(true);
+        assert_eq!(reader.fill_buf().unwrap(), &[5]);
+        assert_eq!(reader.try_fill_buf().unwrap(), Some(&[5][..]));
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6]);
+    }
+
+    #[test]
+    fn test_acc_reader_as_cursor() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3];
+        let mut reader = AccReader::with_increment(5, inner);
+
+        let mut buf = [0; 2];
+        reader.read_exact(&mut buf).unwrap();
+        reader.fill_buf().unwrap();
+
+        let mut cursor = reader.as_cursor();
+        assert_eq!(cursor.position(), 2);
+        let mut speculative = Vec::new();
+        cursor.read_to_end(&mut speculative).unwrap();
+        assert_eq!(speculative, reader.buffer());
+        cursor.seek(SeekFrom::Start(0)).unwrap();
+        cursor.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [5, 6]);
+
+        assert_eq!(reader.position(), 2);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(&rest[..speculative.len()], &speculative[..]);
+        assert_eq!(rest, [7, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_acc_reader_with_transform() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let transformed = Arc::new(AtomicUsize::new(0));
+        let counter = transformed.clone();
+        let mut key = 0u8;
+
+        let inner: Vec<u8> = (0..50u8).map(|i| i ^ i.wrapping_mul(3)).collect();
+        let mut reader = AccReader::with_transform(move |chunk: &mut [u8]| {
+            counter.fetch_add(chunk.len(), Ordering::SeqCst);
+            for b in chunk {
+                *b ^= key;
+                key = key.wrapping_add(3);
+            }
+        }, &inner[..]);
+        let expected: Vec<u8> = (0..50).collect();
+
+        let mut buf = [0; 10];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..], expected[..10]);
+        assert_eq!(reader.fill_buf().unwrap(), &expected[10..]);
+        assert_eq!(reader.seek(SeekFrom::Start(5)).unwrap(), 5);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..], expected[5..15]);
+
+        let mut buf = [0; 64];
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        assert_eq!(reader.read(&mut buf).unwrap(), 50);
+        assert_eq!(buf[..50], expected[..]);
+        assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 49);
+        assert_eq!(transformed.load(Ordering::SeqCst), 50);
+
+        // large reads are not split into small chunks
+        let reads = Arc::new(AtomicUsize::new(0));
+        let counter = reads.clone();
+        let inner = vec![0xffu8; 100000];
+        let mut reader = AccReader::with_transform(|chunk: &mut [u8]| chunk.iter_mut().for_each(|b| *b = !*b), &inner[..])
+            .on_source_read(move |_| { counter.fetch_add(1, Ordering::SeqCst); });
+        assert_eq!(reader.seek(SeekFrom::End(0)).unwrap(), 100000);
+        assert!(reads.load(Ordering::SeqCst) < 10);
+        assert!(reader.as_ref().iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_acc_reader_with_progress() {
+        use std::sync::{Arc, Mutex};
+
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let log = progress.clone();
+        let inner: Vec<u8> = (0..10000u32).map(|i| i as u8).collect();
+        let mut reader = AccReader::with_increment(16, &inner[..])
+            .with_progress(move |n| log.lock().unwrap().push(n));
+
+        let mut buf = [0; 8];
+        reader.read_exact(&mut buf).unwrap();
+        assert!(progress.lock().unwrap().is_empty());
+
+        reader.seek(SeekFrom::Start(5000)).unwrap();
+        {
+            let mut progress = progress.lock().unwrap();
+            assert_eq!(progress.last(), Some(&4992));
+            assert!(progress.windows(2).all(|w| w[0] < w[1]));
+            progress.clear();
+        }
+
+        assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 9999);
+        let progress = progress.lock().unwrap();
+        assert!(progress.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(progress.last(), Some(&5000));
+    }
+
+    #[test]
+    fn test_acc_reader_builder_callbacks() {
+        use std::sync::{Arc, Mutex};
+
+        let reads = Arc::new(Mutex::new(Vec::new()));
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let (reads_log, progress_log) = (reads.clone(), progress.clone());
+        let builder = AccReaderBuilder::new()
+            .increment(4)
+            .rewind_window(Some(2))
+            .on_source_read(move |n| reads_log.lock().unwrap().push(n))
+            .progress(move |n| progress_log.lock().unwrap().push(n));
+
+        let inner: Vec<u8> = (0..10).collect();
+        let mut reader = builder.clone().build(&inner[..]);
+        assert_eq!(reader.fill_buf().unwrap(), &[0, 1, 2, 3]);
+        assert_eq!(*reads.lock().unwrap(), [4]);
+        reader.seek(SeekFrom::Start(9)).unwrap();
+        assert_eq!(*progress.lock().unwrap(), [5]);
+
+        let mut reader = builder.build(&inner[..6]);
+        reader.fill_buf().unwrap();
+        assert_eq!(*reads.lock().unwrap(), [4, 5, 4]);
+    }
+
+    #[cfg(feature = "hashing")]
+    #[test]
+    fn test_acc_reader_builder_hasher() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let builder = AccReaderBuilder::new().increment(2).hasher(DefaultHasher::new());
+        let mut expected = DefaultHasher::new();
+        expected.write(&[5, 6, 7]);
+
+        for _ in 0..2 {
+            let inner: &[u8] = &[5, 6, 7];
+            let mut reader = builder.clone().build(inner);
+            reader.seek(SeekFrom::End(0)).unwrap();
+            assert_eq!(reader.finish_hash(), Some(expected.finish()));
+        }
+    }
+
+    #[test]
+    fn test_acc_reader_with_prefill() {
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6]), Ok(vec![7, 0, 1])]);
+        let mut reader = AccReader::with_prefill(4, inner).unwrap();
+        assert_eq!(reader.stream_position().unwrap(), 0);
+        assert_eq!(reader.buffer(), &[5, 6, 7, 0]);
+
+        let inner: &[u8] = &[5, 6];
+        let reader = AccReader::with_prefill(4, inner).unwrap();
+        assert_eq!(reader.buffer(), &[5, 6]);
+        assert!(reader.is_fully_buffered());
+
+        let inner = ScriptedReader::new(vec![would_block()]);
+        assert!(AccReader::with_prefill(4, inner).is_err());
+    }
+
+    #[test]
+    fn test_acc_reader_seek_error_evicted() {
+        fn reason(e: io::Error) -> Option<SeekError> {
+            e.get_ref().and_then(|e| e.downcast_ref::<SeekError>()).cloned()
+        }
+
+        let inner: &[u8] = &[0; 64];
+        let mut reader = AccReaderBuilder::new().increment(8).rewind_window(Some(4)).build(inner);
+        reader.seek(SeekFrom::Start(40)).unwrap();
+        reader.fill_buf().unwrap();
+
+        let e = reader.seek(SeekFrom::Current(-5)).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reason(e), Some(SeekError::Evicted));
+        assert_eq!(reason(reader.seek(SeekFrom::Current(-41)).unwrap_err()), Some(SeekError::BeforeStart));
+        assert_eq!(reason(reader.cursor_at(0).unwrap_err()), Some(SeekError::Evicted));
+
+        reader.clear();
+        assert_eq!(reason(reader.seek(SeekFrom::Start(47)).unwrap_err()), Some(SeekError::Evicted));
+        assert_eq!(reason(reader.seek(SeekFrom::End(-65)).unwrap_err()), Some(SeekError::BeforeStart));
+        assert_eq!(reader.seek(SeekFrom::End(-16)).unwrap(), 48);
+    }
+
+    #[test]
+    fn test_acc_reader_drop_consumed() {
+        let inner: Vec<u8> = (0..20).collect();
+        let mut reader = AccReader::with_increment(4, &inner[..]);
+
+        let mut buf = [0; 6];
+        reader.read_exact(&mut buf).unwrap();
+        reader.fill_buf().unwrap();
+        reader.drop_consumed();
+        assert_eq!(reader.stream_position().unwrap(), 6);
+        assert_eq!(reader.as_ref(), &[6, 7, 8, 9]);
+
+        assert_eq!(reader.seek(SeekFrom::Start(7)).unwrap(), 7);
+        reader.read_exact(&mut buf[..2]).unwrap();
+        assert_eq!(buf[..2], [7, 8]);
+        assert_eq!(reader.seek(SeekFrom::Start(15)).unwrap(), 15);
+        reader.read_exact(&mut buf[..2]).unwrap();
+        assert_eq!(buf[..2], [15, 16]);
+
+        assert_eq!(reader.seek(SeekFrom::Start(6)).unwrap(), 6);
+        reader.read_exact(&mut buf[..1]).unwrap();
+        assert_eq!(buf[0], 6);
+        assert_eq!(reader.seek(SeekFrom::Start(5)).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 19);
+        assert_eq!(reader.as_ref().len(), 14);
+    }
+
+    #[test]
+    fn test_acc_reader_new_seekable() {
+        use std::io::Cursor;
+
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let mut source = Cursor::new(&data[..]);
+        source.set_position(1000);
+        let mut reader = AccReader::new_seekable(source).unwrap();
+
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..], data[1000..1004]);
+
+        assert_eq!(reader.seek(SeekFrom::End(-4)).unwrap(), 98_996);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..], data[99_996..]);
+        assert!(reader.as_ref().len() < 100);
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+
+        // back into the discarded data, then within the buffer from the end
+        assert_eq!(reader.seek(SeekFrom::Start(2)).unwrap(), 2);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..], data[1002..1006]);
+        assert_eq!(reader.seek(SeekFrom::End(-98_997)).unwrap(), 3);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..], data[1003..1007]);
+        assert_eq!(reader.buffer_start_offset(), 2);
+        assert_eq!(reader.seek(SeekFrom::End(-98_999)).unwrap(), 1);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..], data[1001..1005]);
+        assert_eq!(reader.seek(SeekFrom::End(-99_001)).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest[..], data[1005..]);
+        assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 98_999);
+    }
+
+    #[test]
+    fn test_acc_reader_peak_buffered() {
+        let inner: Vec<u8> = (0..100).collect();
+        let mut reader = AccReaderBuilder::new().increment(10).rewind_window(Some(5)).build(&inner[..]);
+        assert_eq!(reader.peak_buffered(), 0);
+
+        reader.seek(SeekFrom::Start(30)).unwrap();
+        assert_eq!(reader.peak_buffered(), 30);
+        reader.drop_consumed();
+        assert_eq!(reader.as_ref().len(), 0);
+        assert_eq!(reader.peak_buffered(), 30);
+
+        for _ in 0..5 {
+            let n = reader.fill_buf().unwrap().len();
+            reader.consume(n);
+        }
+        assert!(reader.as_ref().len() < 30);
+        assert_eq!(reader.peak_buffered(), 30);
+
+        reader.push_front(&[0; 40]);
+        assert_eq!(reader.peak_buffered(), reader.as_ref().len());
+    }
+
+    #[test]
+    fn test_acc_reader_source_read_offset() {
+        let inner: Vec<u8> = (0..20).collect();
+        let mut reader = AccReader::with_increment(8, &inner[..]);
+        assert_eq!(reader.source_read_offset(), 0);
+
+        assert_eq!(reader.fill_buf().unwrap().len(), 8);
         reader.consume(3);
-        assert_eq!(reader.fill_buf().ok(), Some(&[3, 4][..]));
-        reader.consume(2);
-        assert_eq!(reader.fill_buf().ok(), Some(&[][..]));
+        assert_eq!(reader.position(), 3);
+        assert_eq!(reader.source_read_offset(), 8);
+
+        reader.seek(SeekFrom::Start(10)).unwrap();
+        reader.drop_consumed();
+        assert_eq!(reader.position(), 10);
+        assert_eq!(reader.source_read_offset(), 10);
+        reader.fill_buf().unwrap();
+        assert_eq!(reader.source_read_offset(), 18);
     }
 
     #[test]
-    fn test_acc_reader_seek() {
-        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+    fn test_acc_reader_with_buffer() {
+        let mut buf = Vec::with_capacity(64);
+        buf.extend_from_slice(&[9, 9, 9]);
+        let ptr = buf.as_ptr();
+
+        for inner in [&[5, 6, 7][..], &[0, 1]] {
+            let mut reader = AccReader::with_buffer(buf, inner);
+            assert!(reader.as_ref().is_empty());
+            assert_eq!(reader.capacity(), 64);
+
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data).unwrap();
+            assert_eq!(data, inner);
+            let (parts_buf, _) = reader.into_parts();
+            assert_eq!(parts_buf, inner);
+            buf = parts_buf;
+        }
+        assert_eq!(buf.capacity(), 64);
+        assert_eq!(buf.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_acc_reader_consume_up_to() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::with_increment(4, inner);
+
+        assert_eq!(reader.consume_up_to(3), 0);
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6, 7, 0]);
+        assert_eq!(reader.consume_up_to(3), 3);
+        assert_eq!(reader.consume_up_to(3), 1);
+        assert_eq!(reader.stream_position().unwrap(), 4);
+        assert_eq!(reader.fill_buf().unwrap(), &[1, 2]);
+
+        reader.seek(SeekFrom::Start(1)).unwrap();
+        assert_eq!(reader.consume_up_to(100), 5);
+        assert_eq!(reader.stream_position().unwrap(), 6);
+    }
+
+    #[test]
+    fn test_acc_reader_buffer_start_offset() {
+        let inner: Vec<u8> = (0..20).collect();
+        let mut reader = AccReader::with_increment(4, &inner[..]);
+        assert_eq!(reader.buffer_start_offset(), 0);
+
+        let mut buf = [0; 6];
+        reader.read_exact(&mut buf).unwrap();
+        reader.fill_buf().unwrap();
+        let buffered = reader.as_ref().len() as u64;
+        reader.drop_consumed();
+        assert_eq!(reader.buffer_start_offset(), 6);
+        assert_eq!(reader.buffer_start_offset() + reader.as_ref().len() as u64, buffered);
+        assert_eq!(inner[reader.buffer_start_offset() as usize], reader.as_ref()[0]);
+
+        reader.set_origin(2);
+        assert_eq!(reader.buffer_start_offset(), 6);
+    }
+
+    #[test]
+    fn test_acc_reader_is_empty() {
+        let inner: &[u8] = &[];
         let mut reader = AccReader::new(inner);
+        assert!(reader.is_empty().unwrap());
+        assert!(reader.is_fully_buffered());
 
-        let mut buf = [0, 0];
+        let inner: &[u8] = &[5];
+        let mut reader = AccReader::new(inner);
+        assert!(!reader.is_empty().unwrap());
+        assert_eq!(reader.stream_position().unwrap(), 0);
 
-        assert_eq!(reader.seek(SeekFrom::Start(2)).unwrap(), 2);
+        reader.consume(1);
+        assert!(!reader.has_data_left().unwrap());
+        assert!(!reader.is_empty().unwrap());
+
+        let inner: &[u8] = &[5];
+        let mut reader = AccReader::with_max_lookahead(0, inner);
+        assert!(!reader.is_empty().unwrap());
+    }
+
+    #[test]
+    fn test_acc_reader_replace_source() {
+        let mut reader = AccReader::new(ScriptedReader::new(vec![Ok(vec![5, 6, 7]), would_block()]));
+
+        reader.seek(SeekFrom::Start(2)).unwrap();
+        assert!(reader.seek(SeekFrom::Start(4)).is_err());
+        let old = reader.replace_source(ScriptedReader::new(vec![Ok(vec![0, 1, 2])]));
+        assert!(old.0.is_empty());
+
+        assert_eq!(reader.seek(SeekFrom::End(-2)).unwrap(), 4);
+        assert_eq!(reader.stream_position().unwrap(), 4);
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, [5, 6, 7, 0, 1, 2]);
+
+        let inner: &[u8] = &[5];
+        let mut reader = AccReader::new(inner);
+        reader.seek(SeekFrom::End(0)).unwrap();
+        assert!(reader.is_fully_buffered());
+        let more: &[u8] = &[6];
+        reader.replace_source(more);
+        assert!(!reader.is_fully_buffered());
+        assert_eq!(reader.fill_buf().unwrap(), &[6]);
+    }
+
+    #[cfg(feature = "byteorder")]
+    #[test]
+    fn test_acc_reader_read_int() {
+        let inner = ScriptedReader::new(vec![
+            Ok(vec![0x01]), Ok(vec![0x02, 0x03]), Ok(vec![0x04, 0x05, 0x06, 0x07]), Ok(vec![0x08, 0xff]),
+            Ok(vec![0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]), Ok(vec![0xff]),
+        ]);
+        let mut reader = AccReader::new(inner);
+
+        assert_eq!(reader.read_u16_le().unwrap(), 0x0201);
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        assert_eq!(reader.read_u16_be().unwrap(), 0x0102);
+        assert_eq!(reader.read_u32_be().unwrap(), 0x03040506);
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        assert_eq!(reader.read_u64_le().unwrap(), 0x0807060504030201);
+        reader.seek(SeekFrom::Start(2)).unwrap();
+        assert_eq!(reader.read_u32_le().unwrap(), 0x06050403);
+        assert_eq!(reader.read_i16_be().unwrap(), 0x0708);
+        assert_eq!(reader.read_i16_le().unwrap(), -257);
+        reader.seek(SeekFrom::Current(-1)).unwrap();
+        assert_eq!(reader.read_i64_le().unwrap(), -2);
+        assert_eq!(reader.read_i32_be().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        reader.seek(SeekFrom::Current(-4)).unwrap();
+        assert_eq!(reader.read_i32_le().unwrap(), -1);
+        reader.seek(SeekFrom::Start(9)).unwrap();
+        assert_eq!(reader.read_i64_be().unwrap(), -0x0100000000000001);
+        reader.seek(SeekFrom::Start(9)).unwrap();
+        assert_eq!(reader.read_u64_be().unwrap(), 0xfeffffffffffffff);
+    }
+
+    #[cfg(feature = "hashing")]
+    #[test]
+    fn test_acc_reader_with_hasher() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::Hasher;
+
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6, 7]), Ok(vec![0, 1]), Ok(vec![2])]);
+        let mut reader = AccReader::with_hasher(DefaultHasher::new(), inner);
+
+        let mut buf = [0; 2];
         reader.read_exact(&mut buf).unwrap();
-        assert_eq!(buf, [7, 0]);
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        reader.fill_buf().unwrap();
+        reader.seek(SeekFrom::Start(4)).unwrap();
+        reader.seek(SeekFrom::End(0)).unwrap();
 
-        assert_eq!(reader.seek(SeekFrom::Current(-1)).unwrap(), 3);
+        let mut expected = DefaultHasher::new();
+        expected.write(&[5, 6]);
+        expected.write(&[7, 0, 1, 2]);
+        assert_eq!(reader.finish_hash(), Some(expected.finish()));
+
+        let inner: &[u8] = &[];
+        assert_eq!(AccReader::new(inner).finish_hash(), None);
+    }
+
+    #[test]
+    fn test_acc_reader_seek_buffered() {
+        fn reason(e: io::Error) -> Option<SeekError> {
+            e.get_ref().and_then(|e| e.downcast_ref::<SeekError>()).cloned()
+        }
+
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::with_increment(4, inner);
+        assert_eq!(reader.seek_buffered(SeekFrom::End(0)).unwrap(), 0);
+        assert_eq!(reason(reader.seek_buffered(SeekFrom::Start(1)).unwrap_err()), Some(SeekError::BeyondEnd));
+
+        reader.fill_buf().unwrap();
+        assert_eq!(reader.seek_buffered(SeekFrom::Start(3)).unwrap(), 3);
+        assert_eq!(reader.seek_buffered(SeekFrom::Current(-2)).unwrap(), 1);
+        assert_eq!(reader.seek_buffered(SeekFrom::End(-1)).unwrap(), 3);
+        assert_eq!(reader.seek_buffered(SeekFrom::End(0)).unwrap(), 4);
+        assert_eq!(reason(reader.seek_buffered(SeekFrom::Current(1)).unwrap_err()), Some(SeekError::BeyondEnd));
+        assert_eq!(reason(reader.seek_buffered(SeekFrom::End(-5)).unwrap_err()), Some(SeekError::BeforeStart));
+        assert_eq!(reader.stream_position().unwrap(), 4);
+        assert_eq!(reader.as_ref(), &[5, 6, 7, 0]);
+
+        reader.clear();
+        assert_eq!(reason(reader.seek_buffered(SeekFrom::Start(3)).unwrap_err()), Some(SeekError::Evicted));
+    }
+
+    #[test]
+    fn test_acc_reader_read_to_string() {
+        let inner: &[u8] = "caf\u{e9}!".as_bytes();
+        let mut reader = AccReader::with_increment(2, inner);
+        reader.fill_buf().unwrap();
+        reader.consume(1);
+
+        let mut s = "x".to_owned();
+        assert_eq!(reader.read_to_string(&mut s).unwrap(), 5);
+        assert_eq!(s, "xaf\u{e9}!");
+        assert_eq!(reader.read_to_string(&mut s).unwrap(), 0);
+
+        let inner: &[u8] = &[b'a', b'b', 0xc3];
+        let mut reader = AccReader::new(inner);
+        let mut s = "x".to_owned();
+        let e = reader.read_to_string(&mut s).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(s, "x");
+        assert_eq!(reader.stream_position().unwrap(), 0);
+        assert_eq!(reader.as_ref(), &[b'a', b'b', 0xc3]);
+    }
+
+    #[test]
+    fn test_acc_reader_read_budget() {
+        let inner: &[u8] = &[5, 6, 7, 0];
+        let mut reader = AccReader::with_read_budget(4, inner);
+
+        let mut buf = [0; 4];
         reader.read_exact(&mut buf).unwrap();
-        assert_eq!(buf, [0, 1]);
+        assert_eq!(buf, [5, 6, 7, 0]);
+        let e = reader.read(&mut buf).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::Other);
+        assert!(reader.fill_buf().is_err());
 
-        assert_eq!(reader.seek(SeekFrom::End(-3)).unwrap(), 5);
+        reader.seek(SeekFrom::Start(1)).unwrap();
+        assert_eq!(reader.fill_buf().unwrap(), &[6, 7, 0]);
+    }
+
+    #[test]
+    fn test_acc_reader_read_budget_exceeded() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1];
+        let mut reader = AccReaderBuilder::new().increment(3).read_budget(Some(4)).build(inner);
+
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6, 7]);
+        assert_eq!(reader.seek(SeekFrom::Start(5)).unwrap_err().kind(), io::ErrorKind::Other);
+        assert_eq!(reader.as_ref(), &[5, 6, 7, 0]);
+        assert_eq!(reader.seek(SeekFrom::End(0)).unwrap_err().kind(), io::ErrorKind::Other);
+        assert_eq!(reader.seek(SeekFrom::Start(4)).unwrap(), 4);
+        assert!(!reader.is_fully_buffered());
+    }
+
+    #[test]
+    fn test_acc_reader_known_length() {
+        let inner: Vec<u8> = (0..100).collect();
+        let mut reader = AccReaderBuilder::new().increment(4).known_length(Some(100)).build(&inner[..]);
+
+        assert_eq!(reader.seek(SeekFrom::End(-98)).unwrap(), 2);
+        assert_eq!(reader.as_ref(), &[0, 1]);
+        assert_eq!(reader.seek(SeekFrom::End(-101)).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+        let inner: &[u8] = &[5, 6, 7, 0];
+        let mut reader = AccReader::with_known_length(6, inner);
+        assert_eq!(reader.seek(SeekFrom::End(-3)).unwrap(), 3);
+        let mut buf = [0; 1];
         reader.read_exact(&mut buf).unwrap();
-        assert_eq!(buf, [2, 3]);
+        assert_eq!(buf, [0]);
 
-        // seek to beyond the end and to before the start
-        assert_eq!(reader.seek(SeekFrom::End(3)).err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
-        assert_eq!(reader.seek(SeekFrom::Current(-128)).err().unwrap().kind(), io::ErrorKind::InvalidInput);
+        let e = reader.seek(SeekFrom::End(-1)).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(e.get_ref().unwrap().downcast_ref::<SeekError>().is_none());
 
-        // seek to the end from the start
+        // the real length is used once it is known
+        assert!(reader.is_fully_buffered());
+        assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_acc_reader_peek_buffered() {
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6, 7])]);
         let mut reader = AccReader::new(inner);
-        assert_eq!(reader.seek(SeekFrom::Start(inner.len() as u64)).unwrap(), inner.len() as u64);
+        assert_eq!(reader.peek_buffered(), &[]);
+
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6, 7]);
+        reader.consume(1);
+        assert_eq!(reader.peek_buffered(), &[6, 7]);
+        reader.consume(2);
+        assert_eq!(reader.peek_buffered(), &[]);
+        assert!(!reader.is_fully_buffered());
+    }
+
+    #[test]
+    fn test_acc_reader_retry_interrupted() {
+        fn interrupted() -> io::Result<Vec<u8>> {
+            Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted"))
+        }
+
+        let inner = ScriptedReader::new(vec![interrupted(), Ok(vec![5, 6]), interrupted(), Ok(vec![7])]);
+        let mut reader = AccReader::new(inner);
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6]);
+        reader.consume(2);
+        let mut buf = [0; 4];
+        assert_eq!(reader.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], 7);
+
+        let inner = ScriptedReader::new(vec![interrupted(), Ok(vec![5, 6]), interrupted(), Ok(vec![7])]);
+        let mut reader = AccReader::new(inner).with_retry_interrupted(false);
+        assert_eq!(reader.fill_buf().unwrap_err().kind(), io::ErrorKind::Interrupted);
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6]);
+        reader.consume(2);
+        assert_eq!(reader.read(&mut buf).unwrap_err().kind(), io::ErrorKind::Interrupted);
+        assert_eq!(reader.read(&mut buf).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_acc_reader_eof_policy() {
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6]), Ok(vec![]), Ok(vec![7])]);
+        let mut reader = AccReader::new(inner);
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).unwrap();
+        assert_eq!(data, [5, 6]);
+
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6]), Ok(vec![]), Ok(vec![7]), Ok(vec![]), Ok(vec![])]);
+        let mut reader = AccReader::new(inner).with_eof_policy(EofPolicy::Retry(1));
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6]);
+        reader.consume(2);
+        assert_eq!(reader.fill_buf().unwrap(), &[7]);
+        reader.consume(1);
+        assert!(reader.fill_buf().unwrap().is_empty());
+        assert!(reader.is_fully_buffered());
+
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6]), Ok(vec![]), Ok(vec![7])]);
+        let mut reader = AccReader::new(inner).with_eof_policy(EofPolicy::Retry(3));
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).unwrap();
+        assert_eq!(data, [5, 6, 7]);
+        assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_acc_reader_eager_fill() {
+        let data: Vec<u8> = (0..40).collect();
+        let inner = ScriptedReader::new(data.chunks(1).map(|c| Ok(c.to_vec())).collect());
+        let mut reader = AccReader::with_increment(4, inner).with_eager_fill(16);
+
+        assert_eq!(reader.fill_buf().unwrap(), &data[..16]);
+        reader.consume(10);
+        assert_eq!(reader.fill_buf().unwrap(), &data[10..26]);
+        reader.consume(16);
+        assert_eq!(reader.fill_buf().unwrap(), &data[26..40]);
+
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6]), would_block(), Ok(vec![7])]);
+        let mut reader = AccReader::new(inner).with_eager_fill(4);
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6]);
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6, 7]);
+
+        let error = Err(io::Error::new(io::ErrorKind::InvalidData, "broken"));
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6]), error, Ok(vec![7])]);
+        let mut reader = AccReader::new(inner).with_eager_fill(4);
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6]);
+        assert_eq!(reader.fill_buf().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6, 7]);
+
+        let inner = ScriptedReader::new(data.chunks(1).map(|c| Ok(c.to_vec())).collect());
+        let mut reader = AccReader::with_max_lookahead(8, inner).with_eager_fill(16);
+        assert_eq!(reader.fill_buf().unwrap(), &data[..8]);
+    }
+
+    #[test]
+    fn test_acc_reader_set_position() {
+        let inner: Vec<u8> = (0..20).collect();
+        let mut reader = AccReaderBuilder::new().increment(4).rewind_window(Some(2)).build(&inner[..]);
+
+        reader.set_position(5).unwrap();
+        assert_eq!(reader.position(), 5);
+        assert_eq!(reader.as_ref(), &[0, 1, 2, 3, 4]);
+        reader.set_position(2).unwrap();
+        assert_eq!(reader.position(), 2);
+
+        reader.set_position(12).unwrap();
+        reader.fill_buf().unwrap();
+        reader.set_position(0).unwrap();
+        assert_eq!(reader.position(), 10);
+        let mut buf = [0];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [10]);
+
+        reader.set_position(25).unwrap();
+        assert_eq!(reader.position(), 20);
         assert_eq!(reader.read(&mut buf).unwrap(), 0);
 
-        // seek to beyond the end from the start
+        let inner: &[u8] = &[5, 6, 7];
         let mut reader = AccReader::new(inner);
-        assert_eq!(reader.seek(SeekFrom::Start(128)).err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+        reader.set_position(1 << 40).unwrap();
+        assert_eq!(reader.position(), 3);
+        reader.set_position(u64::MAX).unwrap();
+        assert_eq!(reader.position(), 3);
+    }
+
+    #[test]
+    fn test_acc_reader_skip() {
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6, 7]), Ok(vec![0, 1]), Ok(vec![2])]);
+        let mut reader = AccReader::new(inner);
+
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6, 7]);
+        reader.consume(1);
+        assert_eq!(reader.skip(3).unwrap(), 3);
+        assert_eq!(reader.position(), 4);
+        assert_eq!(reader.skip(0).unwrap(), 0);
+        assert_eq!(reader.skip(5).unwrap(), 2);
+        assert_eq!(reader.position(), 6);
+
+        reader.seek(SeekFrom::Start(2)).unwrap();
+        let mut buf = [0; 3];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [7, 0, 1]);
+
+        let inner: &[u8] = &[5, 6, 7];
+        let mut reader = AccReader::new(inner);
+        assert_eq!(reader.skip(u64::MAX).unwrap(), 3);
+        assert_eq!(reader.position(), 3);
+        assert!(reader.capacity() < 1 << 30);
+    }
+
+    #[test]
+    fn test_acc_reader_set_origin() {
+        let inner: Vec<u8> = (0..20).collect();
+        let mut reader = AccReader::with_increment(4, &inner[..]);
+        let mut buf = [0; 2];
+
+        reader.seek(SeekFrom::Start(10)).unwrap();
+        reader.set_origin(8);
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.seek(SeekFrom::Start(3)).unwrap(), 3);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [11, 12]);
+        assert_eq!(reader.stream_position().unwrap(), 5);
+
+        assert_eq!(reader.seek(SeekFrom::Current(-5)).unwrap(), 0);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [8, 9]);
+        assert_eq!(reader.seek(SeekFrom::Current(-3)).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.seek(SeekFrom::End(-13)).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.seek(SeekFrom::End(-2)).unwrap(), 10);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [18, 19]);
+
+        assert_eq!(reader.seek_buffered(SeekFrom::Start(1)).unwrap(), 1);
+        assert_eq!(reader.seek_buffered(SeekFrom::Current(-2)).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        reader.set_position(4).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [12, 13]);
+
+        reader.set_origin(0);
+        assert_eq!(reader.stream_position().unwrap(), 14);
+
+        // relative seeks from before the origin
+        reader.seek(SeekFrom::Start(2)).unwrap();
+        reader.set_origin(8);
+        assert_eq!(reader.position(), 0);
+        assert_eq!(reader.seek(SeekFrom::Current(1)).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.seek_buffered(SeekFrom::Current(1)).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.seek(SeekFrom::Current(7)).unwrap(), 1);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [9, 10]);
+
+        reader.set_origin(0);
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        reader.set_origin(4);
+        assert_eq!(reader.seek_buffered(SeekFrom::Current(5)).unwrap(), 1);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [5, 6]);
+    }
+
+    #[test]
+    fn test_acc_reader_stream_position() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+        let mut reader = AccReader::new(inner);
+        assert_eq!(reader.stream_position().unwrap(), 0);
+
+        let mut buf = [0, 0, 0];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(reader.stream_position().unwrap(), 3);
+
+        reader.seek(SeekFrom::End(-1)).unwrap();
+        assert_eq!(reader.stream_position().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_acc_reader_into_inner_with_unread() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = AccReader::with_increment(4, inner);
+
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6, 7, 0]);
+        reader.consume(2);
+        reader.seek(SeekFrom::Start(1)).unwrap();
+        let (unread, source) = reader.into_inner_with_unread();
+        assert_eq!(unread, [6, 7, 0]);
+
+        let mut rest = Vec::new();
+        (&unread[..]).chain(source).read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [6, 7, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_acc_reader_expect_eof() {
+        let inner = ScriptedReader::new(vec![Ok(vec![5, 6]), Ok(vec![7])]);
+        let mut reader = AccReader::new(inner);
+
+        assert_eq!(reader.fill_buf().unwrap(), &[5, 6]);
+        reader.consume(1);
+        assert_eq!(reader.expect_eof().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        reader.consume(1);
+        assert_eq!(reader.expect_eof().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.fill_buf().unwrap(), &[7]);
+        reader.consume(1);
+        reader.expect_eof().unwrap();
+        reader.expect_eof().unwrap();
+    }
+
+    #[test]
+    fn test_acc_reader_into_inner_expect_eof() {
+        let inner: &[u8] = &[5, 6, 7, 0];
+
+        // fully consumed
+        let mut reader = AccReader::new(inner);
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(reader.into_inner_expect_eof().unwrap(), &[][..]);
+
+        // trailing bytes still in the source
+        let mut reader = AccReader::new(inner);
+        let mut buf = [0, 0];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(reader.into_inner_expect_eof().err().unwrap().kind(), io::ErrorKind::InvalidData);
+
+        // trailing bytes already buffered
+        let mut reader = AccReader::new(inner);
+        reader.seek(SeekFrom::End(0)).unwrap();
+        reader.seek(SeekFrom::Start(3)).unwrap();
+        assert_eq!(reader.into_inner_expect_eof().err().unwrap().kind(), io::ErrorKind::InvalidData);
     }
 }
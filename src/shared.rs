@@ -0,0 +1,135 @@
+//! Contains an accumulating reader which can be shared by reference.
+//!
+//! See `SyncAccReader` documentation for more information and examples.
+
+use core::cell::RefCell;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use super::io::{self, Read, Seek, SeekFrom};
+use super::{AccReader, Buffer};
+
+/// An accumulating reader which can be read and seeked through a shared reference.
+///
+/// `SyncAccReader` wraps an `AccReader` in a `RefCell`, so that `&SyncAccReader` implements
+/// `Read` and `Seek`. This allows several parts of a program which only have a shared reference
+/// to use the same reader and the same accumulated data, unlike creating several readers,
+/// each of which would keep its own buffer. Note that all of them share the current position
+/// too.
+///
+/// This type is meant for single-threaded use: despite its name, it is not `Sync`. The
+/// `RefCell` is only borrowed for the duration of each call, and a call made while another
+/// one is in progress on the same reader, e.g. by an underlying stream which itself reads
+/// from this reader, panics.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Read;
+///
+/// use acc_reader::{AccReader, SyncAccReader};
+///
+/// fn read_byte<R: Read>(mut r: R) -> u8 {
+///     let mut buf = [0];
+///     r.read_exact(&mut buf).unwrap();
+///     buf[0]
+/// }
+///
+/// let input: &[u8] = &[1, 2, 3];
+/// let ar = SyncAccReader::new(AccReader::new(input));
+///
+/// assert_eq!(read_byte(&ar), 1);
+/// assert_eq!(read_byte(&ar), 2);
+/// ```
+pub struct SyncAccReader<R: Read, B: Buffer = Vec<u8>> {
+    inner: RefCell<AccReader<R, B>>,
+}
+
+impl<R: Read, B: Buffer> SyncAccReader<R, B> {
+    /// Wraps the provided accumulating reader.
+    #[inline]
+    pub fn new(reader: AccReader<R, B>) -> SyncAccReader<R, B> {
+        SyncAccReader { inner: RefCell::new(reader) }
+    }
+
+    /// Unwraps this reader, returning the wrapped accumulating reader.
+    #[inline]
+    pub fn into_inner(self) -> AccReader<R, B> {
+        self.inner.into_inner()
+    }
+
+    /// Returns a mutable reference to the wrapped accumulating reader.
+    ///
+    /// Since this requires a mutable reference to this reader, no borrow checks are needed.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut AccReader<R, B> {
+        self.inner.get_mut()
+    }
+}
+
+impl<R: Read, B: Buffer> From<AccReader<R, B>> for SyncAccReader<R, B> {
+    #[inline]
+    fn from(reader: AccReader<R, B>) -> SyncAccReader<R, B> {
+        SyncAccReader::new(reader)
+    }
+}
+
+impl<R: Read, B: Buffer> Read for &SyncAccReader<R, B> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.borrow_mut().read(buf)
+    }
+}
+
+impl<R: Read, B: Buffer> Seek for &SyncAccReader<R, B> {
+    #[inline]
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.borrow_mut().seek(pos)
+    }
+}
+
+impl<R: Read, B: Buffer> Read for SyncAccReader<R, B> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.get_mut().read(buf)
+    }
+}
+
+impl<R: Read, B: Buffer> Seek for SyncAccReader<R, B> {
+    #[inline]
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.get_mut().seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Seek, SeekFrom};
+
+    use super::*;
+
+    #[test]
+    fn test_sync_acc_reader_shared() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let reader = SyncAccReader::new(AccReader::new(inner));
+
+        let mut r1 = &reader;
+        let mut r2 = &reader;
+        let mut buf = [0; 2];
+
+        r1.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [5, 6]);
+        r2.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [7, 0]);
+
+        assert_eq!(r2.seek(SeekFrom::Start(1)).unwrap(), 1);
+        r1.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [6, 7]);
+
+        let mut reader = reader.into_inner();
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [0, 1, 2]);
+    }
+}
@@ -0,0 +1,150 @@
+//! Contains an accumulating reader over data which is already in memory.
+//!
+//! See `SliceAccReader` documentation for more information and examples.
+
+use core::cmp;
+
+use super::io::{self, BufRead, Read, Seek, SeekFrom};
+use super::resolve_buffered;
+
+/// An accumulating reader over a byte slice.
+///
+/// When the whole stream is already in memory, the buffer of `AccReader` only duplicates
+/// it. `SliceAccReader` provides the same `Read`, `BufRead` and `Seek` behavior, including
+/// the seek errors, but uses the slice itself as the buffer and only tracks the current
+/// position, so reading and seeking never copy the data into a separate buffer and never fail
+/// because of I/O errors. `fill_buf()` always returns the whole rest of the slice.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{BufRead, Read, Seek, SeekFrom};
+///
+/// use acc_reader::SliceAccReader;
+///
+/// let input: &[u8] = &[1, 2, 3, 4, 5];
+/// let mut ar = SliceAccReader::new(input);
+///
+/// ar.seek(SeekFrom::End(-2)).unwrap();
+/// assert_eq!(ar.fill_buf().unwrap(), &[4, 5]);
+///
+/// ar.seek(SeekFrom::Start(1)).unwrap();
+/// let mut buf = [0; 2];
+/// ar.read_exact(&mut buf).unwrap();
+/// assert_eq!(buf, [2, 3]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SliceAccReader<'a> {
+    data: &'a [u8],
+    // invariant: pos <= data.len()
+    pos: usize,
+}
+
+impl<'a> SliceAccReader<'a> {
+    /// Creates a new accumulating reader over the provided slice.
+    #[inline]
+    pub fn new(data: &'a [u8]) -> SliceAccReader<'a> {
+        SliceAccReader { data, pos: 0 }
+    }
+
+    /// Unwraps this reader, returning the whole underlying slice.
+    #[inline]
+    pub fn into_inner(self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the part of the slice after the current position.
+    ///
+    /// Unlike `fill_buf()`, the returned slice borrows the underlying data rather than
+    /// this reader.
+    #[inline]
+    pub fn buffer(&self) -> &'a [u8] {
+        &self.data[self.pos..]
+    }
+
+    /// Returns the current position in the slice.
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.pos as u64
+    }
+}
+
+impl<'a> AsRef<[u8]> for SliceAccReader<'a> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.data
+    }
+}
+
+impl<'a> Read for SliceAccReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = cmp::min(self.data.len() - self.pos, buf.len());
+        buf[..n].copy_from_slice(&self.data[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl<'a> BufRead for SliceAccReader<'a> {
+    #[inline]
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(&self.data[self.pos..])
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.pos = cmp::min(self.pos.saturating_add(amt), self.data.len());
+    }
+}
+
+impl<'a> Seek for SliceAccReader<'a> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = resolve_buffered(pos, 0, self.pos as u64, self.data.len() as u64)?;
+        self.pos = target as usize;
+        Ok(target)
+    }
+
+    #[inline]
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.pos as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
+    use super::*;
+    use SeekError;
+
+    #[test]
+    fn test_slice_acc_reader_seek() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2];
+        let mut reader = SliceAccReader::new(inner);
+
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [5, 6, 7, 0]);
+        assert_eq!(reader.buffer(), &[1, 2]);
+
+        assert_eq!(reader.seek(SeekFrom::Current(-3)).unwrap(), 1);
+        assert_eq!(reader.fill_buf().unwrap(), &[6, 7, 0, 1, 2]);
+        reader.consume(2);
+        assert_eq!(reader.position(), 3);
+
+        let e = reader.seek(SeekFrom::End(1)).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(e.get_ref().and_then(|e| e.downcast_ref::<SeekError>()), Some(&SeekError::BeyondEnd));
+        assert_eq!(reader.seek(SeekFrom::Current(-4)).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.stream_position().unwrap(), 3);
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [0, 1, 2]);
+
+        reader.seek(SeekFrom::Start(4)).unwrap();
+        reader.consume(usize::MAX);
+        assert_eq!(reader.position(), 6);
+        assert_eq!(reader.into_inner(), inner);
+    }
+}
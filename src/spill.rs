@@ -0,0 +1,425 @@
+//! Contains an implementation of accumulating reader which spills its buffer to disk.
+//!
+//! See `SpillAccReader` documentation for more information and examples.
+
+use std::cmp;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+use std::mem;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{SeekError, DEFAULT_BUF_INCREMENT};
+
+// Maximum number of bytes requested from the source in one call when buffering
+// for seeks.
+const SEEK_CHUNK: usize = 64 * 1024;
+
+/// An accumulating reader which keeps the beginning of the stream in memory and
+/// spills the rest of it to a temporary file.
+///
+/// `SpillAccReader` provides the same `std::io::Read`, `std::io::BufRead` and
+/// `std::io::Seek` behavior as `AccReader`, but only the first `threshold` bytes of
+/// the stream are kept in memory. Everything read from the underlying stream beyond
+/// that is appended to a temporary file, which is created lazily when the threshold
+/// is first exceeded and removed when the reader is dropped. Reads and seeks
+/// transparently span both parts.
+///
+/// This makes it possible to seek over streams which are too large to be kept in
+/// memory, at the cost of disk I/O for the data beyond the threshold. Seeking using
+/// `SeekFrom::End` still involves reading the whole underlying stream.
+///
+/// When the data after the current position resides in the temporary file, `fill_buf()`
+/// reads up to the increment number of bytes from the file into a separate window buffer.
+///
+/// Since the accumulated data is not contiguous in memory, it can't be provided through
+/// the `Buffer` trait, so this is a separate reader, and the options of `AccReader` are not
+/// available for it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{self, Read, Seek, SeekFrom};
+///
+/// use acc_reader::SpillAccReader;
+///
+/// // keep at most 1 MiB in memory
+/// let mut ar = SpillAccReader::with_spill_to_temp(1024 * 1024, io::stdin());
+///
+/// // read last 12 bytes
+/// ar.seek(SeekFrom::End(-12)).unwrap();
+/// let mut input = Vec::new();
+/// ar.read_to_end(&mut input).unwrap();
+/// ```
+pub struct SpillAccReader<R: Read> {
+    source: R,
+    mem: Vec<u8>,
+    threshold: usize,
+    // invariant: file_len > 0 implies mem.len() == threshold
+    file: Option<TempFile>,
+    file_len: u64,
+    // invariant: pos <= mem.len() + file_len
+    pos: u64,
+    inc: usize,
+    window: Vec<u8>,
+    window_start: u64,
+    // reused for reading from the source; its first `unstored` bytes have been read
+    // from the source, but could not be appended to the accumulated stream yet
+    scratch: Vec<u8>,
+    unstored: usize,
+}
+
+impl<R: Read> SpillAccReader<R> {
+    /// Creates a new spilling accumulating reader from the provided `Read` instance which
+    /// keeps at most `threshold` bytes in memory.
+    ///
+    /// No file is created until the threshold is exceeded.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io;
+    ///
+    /// use acc_reader::SpillAccReader;
+    ///
+    /// let input = io::stdin();
+    /// let mut ar = SpillAccReader::with_spill_to_temp(4096, input);
+    /// ```
+    #[inline]
+    pub fn with_spill_to_temp(threshold: usize, source: R) -> SpillAccReader<R> {
+        SpillAccReader {
+            source,
+            mem: Vec::new(),
+            threshold,
+            file: None,
+            file_len: 0,
+            pos: 0,
+            inc: DEFAULT_BUF_INCREMENT,
+            window: Vec::new(),
+            window_start: 0,
+            scratch: Vec::new(),
+            unstored: 0,
+        }
+    }
+
+    /// Returns the number of bytes accumulated so far, both in memory and on disk.
+    #[inline]
+    pub fn buffered_len(&self) -> u64 {
+        self.mem.len() as u64 + self.file_len
+    }
+
+    /// Returns `true` if some of the accumulated data has been spilled to disk.
+    #[inline]
+    pub fn is_spilled(&self) -> bool {
+        self.file_len > 0
+    }
+
+    /// Unwraps this reader, returning the underlying `Read` instance.
+    ///
+    /// Note that any accumulated data will be lost, and the temporary file, if any,
+    /// will be removed.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+
+    // Appends the provided data to the accumulated stream, spilling to the
+    // temporary file everything which does not fit under the threshold.
+    // Nothing is appended if writing to the file fails.
+    fn append(&mut self, data: &[u8]) -> io::Result<()> {
+        let to_mem = cmp::min(self.threshold - self.mem.len(), data.len());
+
+        let rest = &data[to_mem..];
+        if !rest.is_empty() {
+            if self.file.is_none() {
+                self.file = Some(TempFile::create()?);
+            }
+            let file = &mut self.file.as_mut().unwrap().file;
+            file.seek(SeekFrom::Start(self.file_len))?;
+            file.write_all(rest)?;
+            self.file_len += rest.len() as u64;
+        }
+        self.mem.extend_from_slice(&data[..to_mem]);
+        Ok(())
+    }
+
+    // Performs a single read of at most n bytes from the source and appends
+    // them to the accumulated stream. Returns the number of bytes appended.
+    // If appending fails, the data is kept and appended by the next call instead
+    // of reading from the source, so nothing read from the source is lost.
+    fn read_more(&mut self, n: usize) -> io::Result<usize> {
+        let mut chunk = mem::take(&mut self.scratch);
+        if self.unstored == 0 {
+            let n = cmp::min(n, SEEK_CHUNK);
+            if chunk.len() < n {
+                chunk.resize(n, 0);
+            }
+            let read = loop {
+                match self.source.read(&mut chunk[..n]) {
+                    Ok(read) => break Ok(read),
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => break Err(e),
+                }
+            };
+            match read {
+                Ok(read) => self.unstored = read,
+                Err(e) => {
+                    self.scratch = chunk;
+                    return Err(e);
+                }
+            }
+        }
+
+        let read = self.unstored;
+        let result = self.append(&chunk[..read]);
+        self.scratch = chunk;
+        result?;
+        self.unstored = 0;
+        Ok(read)
+    }
+
+    // Reads from the source until at least target bytes are accumulated or
+    // the source is exhausted.
+    fn read_up_to(&mut self, target: u64) -> io::Result<()> {
+        while self.buffered_len() < target {
+            let n = cmp::min(target - self.buffered_len(), SEEK_CHUNK as u64) as usize;
+            if self.read_more(n)? == 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // Reads everything that's left in the source.
+    fn read_to_end_of_source(&mut self) -> io::Result<()> {
+        while self.read_more(SEEK_CHUNK)? > 0 {}
+        Ok(())
+    }
+
+    // Copies accumulated data starting from the provided position into buf.
+    fn read_at(&mut self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let mem_len = self.mem.len() as u64;
+        if pos < mem_len {
+            let pos = pos as usize;
+            let n = cmp::min(self.mem.len() - pos, buf.len());
+            buf[..n].copy_from_slice(&self.mem[pos..pos + n]);
+            Ok(n)
+        } else {
+            let n = cmp::min(self.buffered_len() - pos, buf.len() as u64) as usize;
+            if n == 0 {
+                return Ok(0);
+            }
+            let file = &mut self.file.as_mut().unwrap().file;
+            file.seek(SeekFrom::Start(pos - mem_len))?;
+            file.read_exact(&mut buf[..n])?;
+            Ok(n)
+        }
+    }
+
+    fn seek_to(&mut self, target: u64) -> io::Result<u64> {
+        if target > self.buffered_len() {
+            self.read_up_to(target)?;
+            if target > self.buffered_len() {  // still not enough
+                return Err(SeekError::BeyondEnd.into());
+            }
+        }
+        self.pos = target;
+        Ok(target)
+    }
+}
+
+impl<R: Read> Read for SpillAccReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.pos == self.buffered_len() && self.read_more(buf.len())? == 0 {
+            return Ok(0);
+        }
+
+        let pos = self.pos;
+        let read = self.read_at(pos, buf)?;
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: Read> BufRead for SpillAccReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos == self.buffered_len() {
+            let inc = self.inc;
+            self.read_more(inc)?;
+        }
+
+        let mem_len = self.mem.len() as u64;
+        if self.pos < mem_len {
+            return Ok(&self.mem[self.pos as usize..]);
+        }
+
+        let window_end = self.window_start + self.window.len() as u64;
+        if self.pos < self.window_start || self.pos >= window_end {
+            let n = cmp::min(self.buffered_len() - self.pos, self.inc as u64) as usize;
+            let mut window = mem::take(&mut self.window);
+            window.resize(n, 0);
+            let pos = self.pos;
+            if let Err(e) = self.read_at(pos, &mut window) {
+                window.clear();  // the window is refilled by the next call
+                self.window = window;
+                return Err(e);
+            }
+            self.window = window;
+            self.window_start = pos;
+        }
+        Ok(&self.window[(self.pos - self.window_start) as usize..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = cmp::min(self.pos.saturating_add(amt as u64), self.buffered_len());
+    }
+}
+
+impl<R: Read> Seek for SpillAccReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match pos {
+            SeekFrom::End(n) => {
+                if n > 0 {
+                    Err(SeekError::BeyondEnd.into())
+                } else {
+                    self.read_to_end_of_source()?;
+
+                    let d = n.unsigned_abs();
+                    if d > self.buffered_len() {
+                        Err(SeekError::BeforeStart.into())
+                    } else {
+                        self.pos = self.buffered_len() - d;
+                        Ok(self.pos)
+                    }
+                }
+            }
+            SeekFrom::Start(n) => self.seek_to(n),
+            SeekFrom::Current(n) if n < 0 => {
+                let d = n.unsigned_abs();
+                if d > self.pos {
+                    Err(SeekError::BeforeStart.into())
+                } else {
+                    self.pos -= d;
+                    Ok(self.pos)
+                }
+            }
+            SeekFrom::Current(n) => {  // n >= 0
+                let target = self.pos + n as u64;
+                self.seek_to(target)
+            }
+        }
+    }
+
+    #[inline]
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.pos)
+    }
+}
+
+// A file in the temporary directory which is removed on drop.
+struct TempFile {
+    file: File,
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn create() -> io::Result<TempFile> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+        loop {
+            let name = format!(
+                "acc_reader-{}-{}-{}.tmp",
+                process::id(), COUNTER.fetch_add(1, Ordering::Relaxed), nanos
+            );
+            let path = env::temp_dir().join(name);
+            match OpenOptions::new().read(true).write(true).create_new(true).open(&path) {
+                Ok(file) => return Ok(TempFile { file, path }),
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
+    use super::*;
+
+    #[test]
+    fn test_spill_acc_reader_read() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4, 8, 9];
+        let mut reader = SpillAccReader::with_spill_to_temp(4, inner);
+
+        let mut buf = [0, 0, 0];
+        assert_eq!(reader.read(&mut buf).unwrap(), 3);
+        assert_eq!(buf, [5, 6, 7]);
+        assert!(!reader.is_spilled());
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, &inner[3..]);
+        assert!(reader.is_spilled());
+        assert_eq!(reader.buffered_len(), inner.len() as u64);
+    }
+
+    #[test]
+    fn test_spill_acc_reader_seek() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4, 8, 9];
+        let mut reader = SpillAccReader::with_spill_to_temp(4, inner);
+
+        let mut buf = [0, 0];
+
+        assert_eq!(reader.seek(SeekFrom::Start(7)).unwrap(), 7);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [4, 8]);
+
+        // across the in-memory and on-disk parts
+        assert_eq!(reader.seek(SeekFrom::Start(3)).unwrap(), 3);
+        let mut buf3 = [0, 0, 0];
+        reader.read_exact(&mut buf3).unwrap();
+        assert_eq!(buf3, [0, 1, 2]);
+
+        assert_eq!(reader.seek(SeekFrom::End(-2)).unwrap(), 8);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [8, 9]);
+
+        assert_eq!(reader.seek(SeekFrom::Current(-10)).unwrap(), 0);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [5, 6]);
+
+        assert_eq!(reader.seek(SeekFrom::End(1)).err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.seek(SeekFrom::Current(-128)).err().unwrap().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(reader.seek(SeekFrom::Start(128)).err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_spill_acc_reader_buf_read() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4, 8, 9];
+        let mut reader = SpillAccReader::with_spill_to_temp(4, inner);
+        reader.seek(SeekFrom::End(0)).unwrap();
+        reader.seek(SeekFrom::Start(2)).unwrap();
+
+        assert_eq!(reader.fill_buf().ok(), Some(&[7, 0][..]));
+        reader.consume(2);
+        assert_eq!(reader.fill_buf().ok(), Some(&[1, 2, 3, 4, 8, 9][..]));
+        reader.consume(3);
+        assert_eq!(reader.fill_buf().ok(), Some(&[4, 8, 9][..]));
+        reader.consume(3);
+        assert_eq!(reader.fill_buf().ok(), Some(&[][..]));
+    }
+}